@@ -25,8 +25,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Ok(balance) => {
             println!("Balance API response:");
             println!("Code: {}", balance.code);
-            println!("Ledger Balance: {:.2}", balance.ledger_balance);
-            println!("Available Balance: {:.2}", balance.available_balance);
+            println!("Ledger Balance: {}", balance.ledger_balance);
+            println!("Available Balance: {}", balance.available_balance);
         }
         Err(WepayError::ApiError { code, desc }) => {
             eprintln!("Balance API failed: Code={}, Desc={:?}", code, desc);
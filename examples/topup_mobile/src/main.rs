@@ -2,7 +2,11 @@ use std::env;
 
 use dotenvy::dotenv;
 use serde_json::to_string_pretty;
-use wepay_rs::Wepay;
+use wepay_rs::{
+    Wepay,
+    money::Baht,
+    types::{CallbackUrl, DestRef, MobileNumber},
+};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -22,14 +26,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .build()
         .map_err(|e| format!("Failed to build Wepay client: {}", e))?;
 
+    // ตรวจสอบข้อมูลก่อนส่งคำขอ
+    let dest_ref = DestRef::try_from("TEST0000000000000001")?;
+    let target = MobileNumber::try_from("08XXXXXXXX")?;
+    let callback = CallbackUrl::try_from("https://www.mywebsite.com/wepay_result.php")?;
+
     // ทดสอบ topup mobile API
     match wepay
         .topup_mobile(
-            "TEST0000000000000001",
+            &dest_ref,
             "TRMV",
-            &5f32, // 5 บาท
-            "08XXXXXXXX",
-            "https://www.mywebsite.com/wepay_result.php",
+            Baht::from_baht(5), // 5 บาท
+            &target,
+            &callback,
         )
         .await
     {
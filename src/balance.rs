@@ -0,0 +1,469 @@
+use crate::Wepay;
+use crate::error::WepayError;
+use crate::money::Baht;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, MissedTickBehavior};
+
+/// Hook ที่ถูกเรียกเมื่อยอดเงินที่ใช้ได้ลดลงต่ำกว่าเกณฑ์
+pub type LowBalanceHook = Arc<dyn Fn(Baht) + Send + Sync>;
+
+/// ตัวป้องกันการส่งคำสั่งซื้อเมื่อยอดเงินไม่พอ เพื่อไม่ให้ถูกปฏิเสธด้วย `InsufficientFunds` (30019)
+///
+/// จดจำยอดเงินที่ใช้ได้ล่าสุดจาก `Wepay::balance()` และ `balance` ของทุก `BillCommon`
+/// แล้วกันยอดเงินไว้ให้คำสั่งซื้อที่กำลังส่ง คำสั่งซื้อที่ทำให้ยอดคงเหลือที่คาดไว้ติดลบจะถูกปฏิเสธทันที
+///
+/// การ clone จะใช้ยอดเงินและยอดที่กันไว้ร่วมกัน
+#[derive(Clone, Default)]
+pub struct BalanceGuard {
+    state: Arc<Mutex<State>>,
+    low_balance: Option<(Baht, LowBalanceHook)>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    available: Option<Baht>,
+    reserved: Baht,
+}
+
+impl BalanceGuard {
+    /// สร้างตัวป้องกันที่ยังไม่ทราบยอดเงิน (`Wepay` จะดึงยอดเงินก่อนส่งคำสั่งซื้อแรก)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// กำหนด hook ที่ถูกเรียกพร้อมยอดเงินที่ใช้ได้ เมื่อยอดเงินลดลงต่ำกว่า `threshold`
+    ///
+    /// hook จะถูกเรียกครั้งเดียวต่อการลดลงต่ำกว่าเกณฑ์ และจะถูกเรียกอีกครั้งหลังยอดเงินกลับมาถึงเกณฑ์แล้วเท่านั้น
+    pub fn on_low_balance(
+        mut self,
+        threshold: Baht,
+        hook: impl Fn(Baht) + Send + Sync + 'static,
+    ) -> Self {
+        self.low_balance = Some((threshold, Arc::new(hook)));
+        self
+    }
+
+    /// ยอดเงินที่ใช้ได้ล่าสุดที่ทราบ
+    pub fn available(&self) -> Option<Baht> {
+        self.lock().available
+    }
+
+    /// ยอดเงินที่กันไว้ให้คำสั่งซื้อที่กำลังส่ง
+    pub fn reserved(&self) -> Baht {
+        self.lock().reserved
+    }
+
+    /// ยอดเงินที่คาดว่าจะใช้ได้หลังคำสั่งซื้อที่กำลังส่งเสร็จสิ้น
+    pub fn projected(&self) -> Option<Baht> {
+        let state = self.lock();
+        state
+            .available
+            .map(|available| available.saturating_sub(state.reserved))
+    }
+
+    /// บันทึกยอดเงินที่ใช้ได้ล่าสุด
+    pub fn update(&self, available: Baht) {
+        let previous = self.lock().available.replace(available);
+
+        // เรียก hook นอก lock เพื่อให้ hook อ่านค่าจาก guard ได้
+        if let Some((threshold, hook)) = &self.low_balance
+            && let Some(BalanceAlert::Below { .. }) = crossing(previous, available, *threshold)
+        {
+            hook(available);
+        }
+    }
+
+    /// ฟังก์ชันภายในสำหรับกันยอดเงินให้คำสั่งซื้อ (คืนยอดเมื่อ `Reservation` ถูก drop)
+    pub(crate) fn reserve(&self, amount: Baht) -> Result<Reservation, WepayError> {
+        let mut state = self.lock();
+        let projected = state
+            .available
+            .map(|available| available.saturating_sub(state.reserved));
+        let insufficient = |available| WepayError::InsufficientBalance {
+            required: amount,
+            available,
+        };
+        if let Some(projected) = projected
+            && projected < amount
+        {
+            return Err(insufficient(projected));
+        }
+        // ยอดที่กันไว้เกินช่วงที่เก็บได้ (เกิดได้เฉพาะเมื่อยังไม่ทราบยอดเงิน)
+        state.reserved = state
+            .reserved
+            .checked_add(amount)
+            .ok_or_else(|| insufficient(projected.unwrap_or(Baht::ZERO)))?;
+
+        Ok(Reservation {
+            state: self.state.clone(),
+            amount,
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// ยอดเงินที่กันไว้ให้คำสั่งซื้อหนึ่งรายการ
+pub(crate) struct Reservation {
+    state: Arc<Mutex<State>>,
+    amount: Baht,
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.reserved = state.reserved.saturating_sub(self.amount);
+    }
+}
+
+/// ยอดเงินที่ตรวจสอบล่าสุดโดย `BalanceWatcher`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BalanceSnapshot {
+    pub ledger_balance: Baht,    // ยอดเงินคงเหลือทั้งหมด
+    pub available_balance: Baht, // ยอดเงินที่ใช้ได้
+    pub checked_at: Instant,     // เวลาที่ตรวจสอบ
+}
+
+/// การแจ้งเตือนจาก `BalanceWatcher` (คำนวณจาก `available_balance`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BalanceAlert {
+    // ยอดเงินลดลงต่ำกว่าเกณฑ์
+    Below {
+        threshold: Baht,
+        balance: Baht,
+    },
+    // ยอดเงินกลับมาถึงเกณฑ์แล้ว (เช่น หลังเติมเงิน)
+    Recovered {
+        threshold: Baht,
+        balance: Baht,
+    },
+    // ยอดเงินลดลงอย่างน้อย `dropped` ภายในช่วงเวลา `window`
+    RapidDrop {
+        dropped: Baht,
+        window: Duration,
+        balance: Baht,
+    },
+}
+
+/// Hook ที่ถูกเรียกเมื่อ `BalanceWatcher` ตรวจพบการแจ้งเตือน
+pub type BalanceAlertHook = Arc<dyn Fn(&BalanceAlert) + Send + Sync>;
+
+/// ตัวตรวจสอบยอดเงินเป็นระยะผ่าน `Wepay::balance()` เพื่อแจ้งเตือนก่อนยอดเงินหมด
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use wepay_rs::{Wepay, balance::BalanceWatcher, money::Baht};
+/// # async fn run(wepay: Wepay) {
+/// let watch = BalanceWatcher::new(wepay)
+///     .interval(Duration::from_secs(300))
+///     .threshold(Baht::from_baht(1000))
+///     .rapid_drop(Baht::from_baht(5000), Duration::from_secs(3600))
+///     .on_alert(|alert| eprintln!("{:?}", alert))
+///     .spawn();
+/// let mut balance = watch.subscribe();
+/// # }
+/// ```
+pub struct BalanceWatcher {
+    wepay: Wepay,
+    interval: Duration,
+    thresholds: Vec<Baht>,
+    rapid_drop: Option<(Baht, Duration)>,
+    hooks: Vec<BalanceAlertHook>,
+}
+
+impl BalanceWatcher {
+    /// สร้างตัวตรวจสอบที่ตรวจสอบยอดเงินทุก 60 วินาที
+    pub fn new(wepay: Wepay) -> Self {
+        BalanceWatcher {
+            wepay,
+            interval: Duration::from_secs(60),
+            thresholds: Vec::new(),
+            rapid_drop: None,
+            hooks: Vec::new(),
+        }
+    }
+
+    /// กำหนดระยะเวลาระหว่างการตรวจสอบแต่ละครั้ง
+    ///
+    /// # Panics
+    ///
+    /// ถ้า `interval` เป็นศูนย์
+    pub fn interval(mut self, interval: Duration) -> Self {
+        assert!(!interval.is_zero(), "interval must be non-zero");
+        self.interval = interval;
+        self
+    }
+
+    /// เพิ่มเกณฑ์ยอดเงินที่ต้องการแจ้งเตือนเมื่อยอดเงินลดลงต่ำกว่าหรือกลับมาถึงเกณฑ์ (กำหนดได้หลายเกณฑ์)
+    pub fn threshold(mut self, threshold: Baht) -> Self {
+        self.thresholds.push(threshold);
+        self
+    }
+
+    /// แจ้งเตือนเมื่อยอดเงินลดลงอย่างน้อย `amount` ภายในช่วงเวลา `window`
+    pub fn rapid_drop(mut self, amount: Baht, window: Duration) -> Self {
+        self.rapid_drop = Some((amount, window));
+        self
+    }
+
+    /// เพิ่ม hook ที่ถูกเรียกเมื่อมีการแจ้งเตือน
+    pub fn on_alert(mut self, hook: impl Fn(&BalanceAlert) + Send + Sync + 'static) -> Self {
+        self.hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// เริ่ม task เบื้องหลังที่ตรวจสอบยอดเงินทันทีและทุก ๆ `interval`
+    ///
+    /// ถ้าตรวจสอบไม่สำเร็จจะข้ามไปตรวจสอบใหม่ในรอบถัดไป task จะหยุดเมื่อ `BalanceWatch` ถูก drop
+    pub fn spawn(self) -> BalanceWatch {
+        let (sender, receiver) = watch::channel(None);
+        let mut detector = Detector::new(self.thresholds, self.rapid_drop);
+        let (wepay, hooks) = (self.wepay, self.hooks);
+        let mut ticker = tokio::time::interval(self.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let task = tokio::spawn(async move {
+            loop {
+                ticker.tick().await;
+                let Ok(balance) = wepay.balance().await else {
+                    continue;
+                };
+
+                let snapshot = BalanceSnapshot {
+                    ledger_balance: balance.ledger_balance,
+                    available_balance: balance.available_balance,
+                    checked_at: Instant::now(),
+                };
+                let alerts = detector.observe(snapshot.available_balance, snapshot.checked_at);
+                sender.send_replace(Some(snapshot));
+
+                for alert in &alerts {
+                    for hook in &hooks {
+                        hook(alert);
+                    }
+                }
+            }
+        });
+
+        BalanceWatch { receiver, task }
+    }
+}
+
+/// ผลลัพธ์ของ `BalanceWatcher::spawn` สำหรับอ่านยอดเงินล่าสุด
+pub struct BalanceWatch {
+    receiver: watch::Receiver<Option<BalanceSnapshot>>,
+    task: JoinHandle<()>,
+}
+
+impl BalanceWatch {
+    /// รับ channel ที่แจ้งทุกครั้งที่ตรวจสอบยอดเงินสำเร็จ (`None` ถ้ายังไม่เคยตรวจสอบสำเร็จ)
+    pub fn subscribe(&self) -> watch::Receiver<Option<BalanceSnapshot>> {
+        self.receiver.clone()
+    }
+
+    /// ยอดเงินที่ตรวจสอบสำเร็จล่าสุด
+    pub fn latest(&self) -> Option<BalanceSnapshot> {
+        *self.receiver.borrow()
+    }
+}
+
+impl Drop for BalanceWatch {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// ฟังก์ชันภายในสำหรับตรวจสอบว่ายอดเงินข้ามเกณฑ์หรือไม่ (ใช้ร่วมกันโดย `BalanceGuard` และ `BalanceWatcher`)
+///
+/// ยอดเงินแรกที่ทราบและต่ำกว่าเกณฑ์นับเป็นการลดลงต่ำกว่าเกณฑ์
+fn crossing(previous: Option<Baht>, balance: Baht, threshold: Baht) -> Option<BalanceAlert> {
+    let was_below = previous.is_some_and(|previous| previous < threshold);
+    if balance < threshold && !was_below {
+        Some(BalanceAlert::Below { threshold, balance })
+    } else if balance >= threshold && was_below {
+        Some(BalanceAlert::Recovered { threshold, balance })
+    } else {
+        None
+    }
+}
+
+/// ตัวตรวจจับการแจ้งเตือนจากยอดเงินที่ตรวจสอบแต่ละครั้ง
+struct Detector {
+    thresholds: Vec<Baht>,
+    rapid_drop: Option<(Baht, Duration)>,
+    previous: Option<Baht>,
+    history: VecDeque<(Instant, Baht)>,
+}
+
+impl Detector {
+    fn new(thresholds: Vec<Baht>, rapid_drop: Option<(Baht, Duration)>) -> Self {
+        Detector {
+            thresholds,
+            rapid_drop,
+            previous: None,
+            history: VecDeque::new(),
+        }
+    }
+
+    fn observe(&mut self, balance: Baht, at: Instant) -> Vec<BalanceAlert> {
+        let mut alerts = Vec::new();
+
+        alerts.extend(
+            self.thresholds
+                .iter()
+                .filter_map(|&threshold| crossing(self.previous, balance, threshold)),
+        );
+
+        if let Some((amount, window)) = self.rapid_drop {
+            self.history
+                .retain(|(checked_at, _)| at.duration_since(*checked_at) <= window);
+
+            // เทียบกับยอดสูงสุดภายในช่วงเวลา และเริ่มนับใหม่หลังแจ้งเตือนเพื่อไม่ให้แจ้งซ้ำ
+            if let Some(peak) = self.history.iter().map(|(_, b)| *b).max()
+                && peak.saturating_sub(balance) >= amount
+            {
+                alerts.push(BalanceAlert::RapidDrop {
+                    dropped: peak.saturating_sub(balance),
+                    window,
+                    balance,
+                });
+                self.history.clear();
+            }
+            self.history.push_back((at, balance));
+        }
+
+        self.previous = Some(balance);
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_reserve_and_release() {
+        let guard = BalanceGuard::new();
+        guard.update(Baht::from_baht(100));
+
+        let first = guard.reserve(Baht::from_baht(60)).unwrap();
+        assert_eq!(guard.projected(), Some(Baht::from_baht(40)));
+
+        // ยอดที่เหลือหลังกันไว้ไม่พอ
+        assert!(matches!(
+            guard.reserve(Baht::from_baht(50)),
+            Err(WepayError::InsufficientBalance { required, available })
+                if required == Baht::from_baht(50) && available == Baht::from_baht(40)
+        ));
+
+        drop(first);
+        assert_eq!(guard.reserved(), Baht::ZERO);
+        assert!(guard.reserve(Baht::from_baht(100)).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_balance_is_not_refused() {
+        let guard = BalanceGuard::new();
+        assert!(guard.reserve(Baht::from_baht(1000)).is_ok());
+        assert_eq!(guard.projected(), None);
+    }
+
+    #[test]
+    fn test_low_balance_hook_fires_once_per_crossing() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let guard = BalanceGuard::new().on_low_balance(Baht::from_baht(100), move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        guard.update(Baht::from_baht(500));
+        guard.update(Baht::from_baht(90)); // ต่ำกว่าเกณฑ์
+        guard.update(Baht::from_baht(80)); // ยังต่ำกว่าเกณฑ์ ไม่เรียกซ้ำ
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        guard.update(Baht::from_baht(200)); // เติมเงินแล้ว
+        guard.update(Baht::from_baht(50));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_crossing() {
+        let threshold = Baht::from_baht(100);
+        let below = |balance| Some(BalanceAlert::Below { threshold, balance });
+        let recovered = |balance| Some(BalanceAlert::Recovered { threshold, balance });
+
+        assert_eq!(
+            crossing(None, Baht::from_baht(99), threshold),
+            below(Baht::from_baht(99))
+        );
+        assert_eq!(crossing(None, Baht::from_baht(100), threshold), None);
+        assert_eq!(
+            crossing(Some(Baht::from_baht(99)), Baht::from_baht(50), threshold),
+            None
+        );
+        assert_eq!(
+            crossing(Some(Baht::from_baht(99)), Baht::from_baht(100), threshold),
+            recovered(Baht::from_baht(100))
+        );
+    }
+
+    #[test]
+    fn test_detector_thresholds() {
+        let mut detector = Detector::new(vec![Baht::from_baht(1000), Baht::from_baht(100)], None);
+        let now = Instant::now();
+
+        assert!(detector.observe(Baht::from_baht(2000), now).is_empty());
+        assert_eq!(
+            detector.observe(Baht::from_baht(500), now),
+            [BalanceAlert::Below {
+                threshold: Baht::from_baht(1000),
+                balance: Baht::from_baht(500),
+            }]
+        );
+        assert!(detector.observe(Baht::from_baht(400), now).is_empty());
+        assert_eq!(
+            detector.observe(Baht::from_baht(50), now),
+            [BalanceAlert::Below {
+                threshold: Baht::from_baht(100),
+                balance: Baht::from_baht(50),
+            }]
+        );
+        assert_eq!(detector.observe(Baht::from_baht(1500), now).len(), 2); // เติมเงินแล้ว
+    }
+
+    #[test]
+    fn test_detector_rapid_drop() {
+        let window = Duration::from_secs(3600);
+        let mut detector = Detector::new(Vec::new(), Some((Baht::from_baht(500), window)));
+        let start = Instant::now();
+
+        assert!(detector.observe(Baht::from_baht(2000), start).is_empty());
+        assert!(
+            detector
+                .observe(Baht::from_baht(1800), start + Duration::from_secs(600))
+                .is_empty()
+        );
+        assert_eq!(
+            detector.observe(Baht::from_baht(1400), start + Duration::from_secs(1200)),
+            [BalanceAlert::RapidDrop {
+                dropped: Baht::from_baht(600),
+                window,
+                balance: Baht::from_baht(1400),
+            }]
+        );
+
+        // ลดลงช้ากว่าช่วงเวลาที่กำหนด ไม่แจ้งเตือน
+        assert!(
+            detector
+                .observe(Baht::from_baht(1000), start + Duration::from_secs(6000))
+                .is_empty()
+        );
+    }
+}
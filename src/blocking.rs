@@ -0,0 +1,253 @@
+//! Client แบบ blocking สำหรับโปรแกรมที่ไม่ได้ใช้ async (เช่น งาน batch หรือ cron)
+//!
+//! `Wepay` ในโมดูลนี้มี builder และฟังก์ชันเช่นเดียวกับ `wepay_rs::Wepay` แต่รอผลลัพธ์ก่อนคืนค่า
+//! โดยใช้ tokio runtime ภายในของตัวเอง จึงไม่ต้องสร้าง runtime เอง
+//!
+//! ห้ามเรียกใช้ (หรือปล่อยให้ instance สุดท้ายถูก drop) ภายใน async context เพราะ tokio จะ panic
+//! ```no_run
+//! use wepay_rs::blocking::Wepay;
+//!
+//! fn main() -> Result<(), wepay_rs::error::WepayError> {
+//!     let wepay = Wepay::builder()
+//!         .username("username")
+//!         .password("password")
+//!         .build()?;
+//!     println!("{:?}", wepay.balance()?);
+//!     Ok(())
+//! }
+//! ```
+
+use crate::balance::BalanceGuard;
+use crate::catalog::Catalog;
+use crate::error::WepayError;
+use crate::idempotency::{OrderStore, Submission};
+use crate::money::Baht;
+use crate::request::{BillpayRefs, GtopupRefs, OrderRequest, TransactionQuery};
+use crate::response;
+use crate::retry::RetryPolicy;
+use crate::transport::{Cassette, Transport};
+use crate::types::{CallbackUrl, DestRef, MobileNumber};
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::{Builder, Runtime};
+
+/// Struct สำหรับสร้างอินสแตนซ์ของ Wepay แบบ blocking (ตัวเลือกเดียวกับ `wepay_rs::WepayBuilder`)
+#[derive(Default)]
+pub struct WepayBuilder {
+    inner: crate::WepayBuilder,
+}
+
+/// Struct หลักที่ใช้เรียกใช้งาน API ต่าง ๆ ของ Wepay แบบ blocking
+///
+/// การ clone จะใช้ runtime, connection pool และตัวจำกัดอัตราการส่งคำขอร่วมกัน
+#[derive(Clone)]
+pub struct Wepay {
+    inner: crate::Wepay,
+    runtime: Arc<Runtime>,
+}
+
+impl WepayBuilder {
+    /// สร้าง instance ใหม่ของ WepayBuilder พร้อมค่าเริ่มต้น
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// กำหนด username ที่จะใช้เรียก API
+    pub fn username(mut self, username: impl Into<String>) -> Self {
+        self.inner = self.inner.username(username);
+        self
+    }
+
+    /// กำหนด password ที่จะใช้เรียก API
+    pub fn password(mut self, password: impl Into<String>) -> Self {
+        self.inner = self.inner.password(password);
+        self
+    }
+
+    /// กำหนด URL หลักสำหรับเรียก API (สามารถปรับเปลี่ยนได้จากค่าเริ่มต้น)
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.inner = self.inner.base_url(base_url);
+        self
+    }
+
+    /// กำหนด proxy สำหรับ client ในกรณีที่ต้องการ
+    pub fn proxy(mut self, proxy_url: impl Into<String>) -> Self {
+        self.inner = self.inner.proxy(proxy_url);
+        self
+    }
+
+    /// กำหนดระยะเวลารอสูงสุดของแต่ละคำขอ (ดู `wepay_rs::WepayBuilder::timeout`)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.inner = self.inner.timeout(timeout);
+        self
+    }
+
+    /// กำหนดนโยบายการส่งคำขอซ้ำเมื่อเกิดข้อผิดพลาดชั่วคราว (ค่าเริ่มต้นคือไม่ส่งซ้ำ)
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.inner = self.inner.retry(policy);
+        self
+    }
+
+    /// จำกัดอัตราการส่งคำขอ (ดู `wepay_rs::WepayBuilder::rate_limit`)
+    ///
+    /// # Panics
+    ///
+    /// ถ้า `requests_per_second` ไม่ใช่ค่าบวก
+    pub fn rate_limit(mut self, requests_per_second: f64, burst: u32) -> Self {
+        self.inner = self.inner.rate_limit(requests_per_second, burst);
+        self
+    }
+
+    /// กำหนดที่เก็บคำสั่งซื้อสำหรับ `Wepay::submit_once` (ค่าเริ่มต้นคือ `MemoryOrderStore`)
+    pub fn order_store(mut self, store: impl OrderStore + 'static) -> Self {
+        self.inner = self.inner.order_store(store);
+        self
+    }
+
+    /// ตรวจสอบจำนวนเงินของคำสั่งซื้อกับรายการสินค้าใน `catalog` ก่อนส่ง (ดู `wepay_rs::WepayBuilder::preflight`)
+    pub fn preflight(mut self, catalog: Catalog) -> Self {
+        self.inner = self.inner.preflight(catalog);
+        self
+    }
+
+    /// ตรวจสอบยอดเงินก่อนส่งคำสั่งซื้อด้วย `guard` (ค่าเริ่มต้นคือไม่ตรวจสอบ)
+    pub fn balance_guard(mut self, guard: BalanceGuard) -> Self {
+        self.inner = self.inner.balance_guard(guard);
+        self
+    }
+
+    /// ส่งคำขอทั้งหมดผ่าน `transport` แทน `ReqwestTransport` (ค่าเริ่มต้น)
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.inner = self.inner.transport(transport);
+        self
+    }
+
+    /// บันทึกคำขอและ response ทุกคู่ลงไฟล์ cassette ที่ `path` (ดู `wepay_rs::WepayBuilder::record`)
+    pub fn record(mut self, path: impl Into<PathBuf>) -> Self {
+        self.inner = self.inner.record(path);
+        self
+    }
+
+    /// ตอบคำขอจาก `cassette` แทนการเชื่อมต่อกับ wepay
+    pub fn replay(mut self, cassette: Cassette) -> Self {
+        self.inner = self.inner.replay(cassette);
+        self
+    }
+
+    /// สร้าง instance ของ Wepay พร้อม runtime ภายในจากค่า configuration ทั้งหมดที่ตั้งไว้
+    pub fn build(self) -> Result<Wepay, WepayError> {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| WepayError::Transport(e.into()))?;
+
+        Ok(Wepay {
+            inner: self.inner.build()?,
+            runtime: Arc::new(runtime),
+        })
+    }
+}
+
+impl Wepay {
+    /// เรียกใช้งาน builder เพื่อสร้าง Wepay instance
+    pub fn builder() -> WepayBuilder {
+        WepayBuilder::new()
+    }
+
+    /// ฟังก์ชันภายในสำหรับรอผลลัพธ์ของ future บน runtime ภายใน
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    /// เขียนคำขอและ response ที่บันทึกไว้ลงไฟล์ cassette ทันที (ดู `wepay_rs::Wepay::flush_recording`)
+    pub fn flush_recording(&self) -> Result<(), WepayError> {
+        self.block_on(self.inner.flush_recording())
+    }
+
+    /// ดึงข้อมูลสินค้าจาก API
+    pub fn product(&self) -> Result<response::Product, WepayError> {
+        self.block_on(self.inner.product())
+    }
+
+    /// ดึงยอดเงินคงเหลือของบัญชีผ่าน API
+    pub fn balance(&self) -> Result<response::Balance, WepayError> {
+        self.block_on(self.inner.balance())
+    }
+
+    /// ตรวจสอบสถานะรายการจาก transaction_id หรือ dest_ref
+    pub fn transaction_status(
+        &self,
+        query: &TransactionQuery,
+    ) -> Result<response::Transaction, WepayError> {
+        self.block_on(self.inner.transaction_status(query))
+    }
+
+    /// ส่งคำสั่งซื้อแบบ idempotent โดยใช้ dest_ref ของ `request` เป็นกุญแจ (ดู `wepay_rs::Wepay::submit_once`)
+    pub fn submit_once<R: OrderRequest>(&self, request: &R) -> Result<Submission, WepayError> {
+        self.block_on(self.inner.submit_once(request))
+    }
+
+    /// ส่งคำสั่งซื้อที่สร้างจาก builder ใน `request` (เช่น `MobileTopupRequest`) ผ่าน API
+    pub fn submit<R: OrderRequest>(&self, request: &R) -> Result<response::BillCommon, WepayError> {
+        self.block_on(self.inner.submit(request))
+    }
+
+    /// เติมเงินมือถือผ่าน API
+    pub fn topup_mobile(
+        &self,
+        dest_ref: &DestRef,
+        company: &str,
+        amount: Baht,
+        target: &MobileNumber,
+        callback: &CallbackUrl,
+    ) -> Result<response::BillCommon, WepayError> {
+        self.block_on(
+            self.inner
+                .topup_mobile(dest_ref, company, amount, target, callback),
+        )
+    }
+
+    /// ชำระบิล (ค่าไฟ ค่าน้ำ บัตรเครดิต ฯลฯ) ผ่าน API
+    pub fn billpay(
+        &self,
+        dest_ref: &DestRef,
+        company: &response::Billpay,
+        amount: Baht,
+        refs: &BillpayRefs,
+        callback: &CallbackUrl,
+    ) -> Result<response::BillCommon, WepayError> {
+        self.block_on(
+            self.inner
+                .billpay(dest_ref, company, amount, refs, callback),
+        )
+    }
+
+    /// ซื้อบัตรเงินสด (PIN) ผ่าน API
+    pub fn cashcard(
+        &self,
+        dest_ref: &DestRef,
+        company: &response::Cashcard,
+        amount: Baht,
+        callback: &CallbackUrl,
+    ) -> Result<response::BillCommon, WepayError> {
+        self.block_on(self.inner.cashcard(dest_ref, company, amount, callback))
+    }
+
+    /// เติมเงินเกมผ่าน API
+    pub fn gtopup(
+        &self,
+        dest_ref: &DestRef,
+        company: &response::Gtopup,
+        amount: Baht,
+        refs: &GtopupRefs,
+        server: Option<&str>,
+        callback: &CallbackUrl,
+    ) -> Result<response::BillCommon, WepayError> {
+        self.block_on(
+            self.inner
+                .gtopup(dest_ref, company, amount, refs, server, callback),
+        )
+    }
+}
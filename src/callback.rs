@@ -0,0 +1,238 @@
+use crate::error::WepayError;
+use crate::money::Baht;
+use crate::response::TransactionStatus;
+use crate::types::DestRef;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use url::form_urlencoded;
+
+#[cfg(feature = "actix")]
+pub mod actix;
+#[cfg(feature = "axum")]
+pub mod axum;
+
+/// ข้อผิดพลาดที่ `CallbackHandler` ส่งกลับได้
+pub type HandlerError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Trait สำหรับประมวลผล callback ที่ได้รับจาก wepay (ใช้ร่วมกับ feature `axum` หรือ `actix`)
+///
+/// ถ้าคืนค่า error ระบบจะตอบกลับ wepay ด้วย HTTP 500 เพื่อให้ wepay ส่ง callback ซ้ำอีกครั้ง
+/// โดยข้อความตอบกลับจะเป็นข้อความคงที่ ส่วนรายละเอียดของ error จะส่งให้ `on_error` แทน
+#[async_trait]
+pub trait CallbackHandler: Send + Sync + 'static {
+    async fn handle(&self, notification: Notification) -> Result<(), HandlerError>;
+
+    /// เรียกเมื่อ `handle` คืนค่า error (ค่าเริ่มต้นจะเขียน error ลง stderr)
+    fn on_error(&self, transaction_id: &str, error: &HandlerError) {
+        eprintln!("wepay callback {transaction_id} failed: {error}");
+    }
+}
+
+// อนุญาตให้ใช้ async closure เป็น handler ได้โดยตรง
+#[async_trait]
+impl<F, Fut> CallbackHandler for F
+where
+    F: Fn(Notification) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), HandlerError>> + Send,
+{
+    async fn handle(&self, notification: Notification) -> Result<(), HandlerError> {
+        self(notification).await
+    }
+}
+
+/// โครงสร้างสำหรับข้อมูลที่ wepay ส่งกลับมาทาง callback (`resp_url`) เมื่อทำรายการเสร็จ
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    pub transaction_id: String,
+    pub dest_ref: DestRef,
+    pub status: TransactionStatus,
+    pub operator_trxnsid: Option<String>, // เลขอ้างอิงจากผู้ให้บริการปลายทาง
+    pub real_amount: Option<Baht>,        // จำนวนเงินที่ทำรายการจริง
+    pub sms: Option<String>,              // ข้อความจากผู้ให้บริการ (รหัส PIN/Serial ของบัตรเงินสด)
+}
+
+impl Notification {
+    /// แปลงข้อมูล form-encoded ที่ wepay POST มาเป็น `Notification`
+    pub fn parse(body: &str) -> Result<Self, WepayError> {
+        let fields: HashMap<String, String> = form_urlencoded::parse(body.as_bytes())
+            .into_owned()
+            .collect();
+        Self::from_fields(&fields)
+    }
+
+    /// แปลงข้อมูลที่แยก key/value แล้ว (เช่น จาก framework ที่ parse form ให้) เป็น `Notification`
+    pub fn from_fields(fields: &HashMap<String, String>) -> Result<Self, WepayError> {
+        // ดึงค่าที่ไม่ว่างเปล่าออกจาก form
+        let get = |key: &str| fields.get(key).map(|v| v.trim()).filter(|v| !v.is_empty());
+
+        let transaction_id = get("transaction_id")
+            .filter(|v| v.chars().all(|c| c.is_ascii_digit()))
+            .ok_or(WepayError::InvalidCallback("transaction_id"))?;
+
+        let dest_ref = get("dest_ref")
+            .and_then(|v| DestRef::try_from(v).ok())
+            .ok_or(WepayError::InvalidCallback("dest_ref"))?;
+
+        let status = get("status")
+            .and_then(TransactionStatus::from_code)
+            .ok_or(WepayError::InvalidCallback("status"))?;
+
+        let real_amount = match get("real_amount") {
+            Some(v) => Some(
+                v.parse::<Baht>()
+                    .map_err(|_| WepayError::InvalidCallback("real_amount"))?,
+            ),
+            None => None,
+        };
+
+        Ok(Notification {
+            transaction_id: transaction_id.to_string(),
+            dest_ref,
+            status,
+            operator_trxnsid: get("operator_trxnsid").map(str::to_string),
+            real_amount,
+            sms: get("sms").map(str::to_string),
+        })
+    }
+
+    /// ตรวจสอบว่า callback นี้เป็นของรายการที่ส่งไปจริง (เทียบ dest_ref และ transaction_id จาก `BillCommon`)
+    pub fn verify(&self, dest_ref: &DestRef, transaction_id: &str) -> Result<(), WepayError> {
+        if &self.dest_ref != dest_ref {
+            return Err(WepayError::InvalidCallback("dest_ref"));
+        }
+
+        if self.transaction_id != transaction_id {
+            return Err(WepayError::InvalidCallback("transaction_id"));
+        }
+
+        Ok(())
+    }
+
+    /// ข้อความตอบกลับที่ wepay ต้องการเพื่อยืนยันว่าได้รับ callback แล้ว
+    pub fn acknowledgement(&self) -> String {
+        format!("SUCCEED|UPDATE_ID={}", self.transaction_id)
+    }
+
+    /// ตรวจสอบว่ารายการสำเร็จหรือไม่
+    pub fn is_success(&self) -> bool {
+        self.status == TransactionStatus::Success
+    }
+}
+
+/// ข้อความตอบกลับ wepay เมื่อ `CallbackHandler` ทำงานไม่สำเร็จ (ไม่เปิดเผยรายละเอียดของ error)
+#[cfg(any(feature = "axum", feature = "actix"))]
+const HANDLER_FAILED: &str = "FAILED";
+
+/// ฟังก์ชันภายในสำหรับประมวลผล body ของ callback และคืนค่า HTTP status พร้อมข้อความตอบกลับ
+#[cfg(any(feature = "axum", feature = "actix"))]
+pub(crate) async fn process<H: CallbackHandler>(handler: &H, body: &[u8]) -> (u16, String) {
+    // body ที่ไม่ใช่ UTF-8 หรือ parse ไม่ได้ถือว่าไม่ใช่ callback จาก wepay
+    let notification = match std::str::from_utf8(body)
+        .map_err(|_| WepayError::InvalidCallback("body"))
+        .and_then(Notification::parse)
+    {
+        Ok(notification) => notification,
+        Err(e) => return (400, e.to_string()),
+    };
+
+    let transaction_id = notification.transaction_id.clone();
+    let ack = notification.acknowledgement();
+    match handler.handle(notification).await {
+        Ok(()) => (200, ack),
+        Err(e) => {
+            handler.on_error(&transaction_id, &e);
+            (500, HANDLER_FAILED.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_success() {
+        let body = "transaction_id=394209456&dest_ref=TEST0000000000000001&status=2&operator_trxnsid=AB1234&real_amount=5.00&sms=";
+        let n = Notification::parse(body).unwrap();
+
+        assert_eq!(n.transaction_id, "394209456");
+        assert_eq!(n.dest_ref.as_str(), "TEST0000000000000001");
+        assert_eq!(n.status, TransactionStatus::Success);
+        assert_eq!(n.operator_trxnsid.as_deref(), Some("AB1234"));
+        assert_eq!(n.real_amount, Some(Baht::from_baht(5)));
+        assert_eq!(n.sms, None); // ค่าว่างถือว่าไม่มีข้อมูล
+        assert!(n.is_success());
+    }
+
+    #[test]
+    fn test_parse_cashcard_pin() {
+        let body = "transaction_id=394209458&dest_ref=TEST03&status=2&sms=PIN%3A1234567890123456+SERIAL%3A0001";
+        let n = Notification::parse(body).unwrap();
+
+        assert_eq!(n.sms.as_deref(), Some("PIN:1234567890123456 SERIAL:0001"));
+    }
+
+    #[test]
+    fn test_parse_failed() {
+        let n = Notification::parse("transaction_id=1&dest_ref=A1&status=4").unwrap();
+        assert_eq!(n.status, TransactionStatus::Failed);
+        assert!(!n.is_success());
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed() {
+        // ไม่มี transaction_id
+        assert!(matches!(
+            Notification::parse("dest_ref=A1&status=2"),
+            Err(WepayError::InvalidCallback("transaction_id"))
+        ));
+        // transaction_id ไม่ใช่ตัวเลข
+        assert!(matches!(
+            Notification::parse("transaction_id=abc&dest_ref=A1&status=2"),
+            Err(WepayError::InvalidCallback("transaction_id"))
+        ));
+        // dest_ref มีอักขระที่ไม่อนุญาต
+        assert!(matches!(
+            Notification::parse("transaction_id=1&dest_ref=A_1&status=2"),
+            Err(WepayError::InvalidCallback("dest_ref"))
+        ));
+        // สถานะที่ไม่รู้จัก
+        assert!(matches!(
+            Notification::parse("transaction_id=1&dest_ref=A1&status=9"),
+            Err(WepayError::InvalidCallback("status"))
+        ));
+        // จำนวนเงินไม่ใช่ตัวเลข
+        assert!(matches!(
+            Notification::parse("transaction_id=1&dest_ref=A1&status=2&real_amount=five"),
+            Err(WepayError::InvalidCallback("real_amount"))
+        ));
+        // ไม่ใช่ข้อมูล form
+        assert!(Notification::parse("<html></html>").is_err());
+    }
+
+    #[test]
+    fn test_acknowledgement() {
+        let n = Notification::parse("transaction_id=394209456&dest_ref=A1&status=2").unwrap();
+        assert_eq!(n.acknowledgement(), "SUCCEED|UPDATE_ID=394209456");
+    }
+
+    #[test]
+    fn test_verify() {
+        let n = Notification::parse("transaction_id=394209456&dest_ref=A1&status=2").unwrap();
+
+        let a1 = DestRef::try_from("A1").unwrap();
+        let a2 = DestRef::try_from("A2").unwrap();
+
+        assert!(n.verify(&a1, "394209456").is_ok());
+        assert!(matches!(
+            n.verify(&a2, "394209456"),
+            Err(WepayError::InvalidCallback("dest_ref"))
+        ));
+        assert!(matches!(
+            n.verify(&a1, "394209457"),
+            Err(WepayError::InvalidCallback("transaction_id"))
+        ));
+    }
+}
@@ -0,0 +1,40 @@
+use super::{CallbackHandler, Notification, process};
+use actix_web::{
+    FromRequest, HttpRequest, HttpResponse, Resource, dev::Payload, error, http::StatusCode, web,
+};
+use std::future::Future;
+use std::pin::Pin;
+
+/// สร้าง `Resource` ที่รับ callback จาก wepay ที่ `path` แล้วส่งต่อให้ `handler`
+///
+/// ตอบกลับ wepay ด้วยข้อความยืนยันเมื่อ `handler` ทำงานสำเร็จ
+pub fn resource<H: CallbackHandler>(path: &str, handler: H) -> Resource {
+    web::resource(path)
+        .app_data(web::Data::new(handler))
+        .route(web::post().to(handle::<H>))
+}
+
+async fn handle<H: CallbackHandler>(handler: web::Data<H>, body: web::Bytes) -> HttpResponse {
+    let (status, body) = process(handler.get_ref(), &body).await;
+    let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    HttpResponse::build(status).body(body)
+}
+
+/// Extractor สำหรับใช้ใน handler ของ actix-web ที่ต้องการจัดการ callback เอง
+pub struct WepayCallback(pub Notification);
+
+impl FromRequest for WepayCallback {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let body = web::Bytes::from_request(req, payload);
+        Box::pin(async move {
+            let body = body.await?;
+            let body = std::str::from_utf8(&body).map_err(error::ErrorBadRequest)?;
+            Notification::parse(body)
+                .map(WepayCallback)
+                .map_err(error::ErrorBadRequest)
+        })
+    }
+}
@@ -0,0 +1,43 @@
+use super::{CallbackHandler, Notification, process};
+use ::axum::{
+    Router,
+    body::Bytes,
+    extract::{FromRequest, Request, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use std::sync::Arc;
+
+/// สร้าง `Router` ที่รับ callback จาก wepay ที่ `path` แล้วส่งต่อให้ `handler`
+///
+/// ตอบกลับ wepay ด้วยข้อความยืนยันเมื่อ `handler` ทำงานสำเร็จ
+pub fn router<H: CallbackHandler>(path: &str, handler: H) -> Router {
+    Router::new()
+        .route(path, post(handle::<H>))
+        .with_state(Arc::new(handler))
+}
+
+async fn handle<H: CallbackHandler>(State(handler): State<Arc<H>>, body: Bytes) -> Response {
+    let (status, body) = process(handler.as_ref(), &body).await;
+    let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    (status, body).into_response()
+}
+
+/// Extractor สำหรับใช้ใน handler ของ axum ที่ต้องการจัดการ callback เอง
+pub struct WepayCallback(pub Notification);
+
+impl<S: Send + Sync> FromRequest<S> for WepayCallback {
+    type Rejection = (StatusCode, String);
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+        let body =
+            std::str::from_utf8(&body).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+        Notification::parse(body)
+            .map(WepayCallback)
+            .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))
+    }
+}
@@ -0,0 +1,244 @@
+use crate::Wepay;
+use crate::error::WepayError;
+use crate::request::OrderRequest;
+use crate::response::Product;
+use crate::transport::HttpResponse;
+use reqwest::header::{ETAG, LAST_MODIFIED};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+mod index;
+mod quote;
+
+pub use index::{Category, Company, ProductIndex};
+pub use quote::Quote;
+
+/// ระยะเวลาที่รายการสินค้าจะถูก cache ไว้ ถ้าไม่ได้กำหนด `CatalogBuilder::ttl`
+const DEFAULT_TTL: Duration = Duration::from_secs(300);
+
+/// ระยะเวลาที่ใช้รายการเดิมต่อโดยไม่ส่งคำขอใหม่ หลังจากการดึงรายการล้มเหลว ถ้าไม่ได้กำหนด
+/// `CatalogBuilder::failure_backoff`
+const DEFAULT_FAILURE_BACKOFF: Duration = Duration::from_secs(30);
+
+/// รายการสินค้าจาก `comp_export.php` ที่ cache ไว้ตามระยะเวลาที่กำหนด (TTL)
+///
+/// ถ้า server ส่ง `ETag`/`Last-Modified` มา การ refresh จะส่ง `If-None-Match`/`If-Modified-Since`
+/// เพื่อไม่ต้องดาวน์โหลดรายการเดิมซ้ำ และถ้าการ refresh ล้มเหลวจะใช้รายการล่าสุดที่ดึงสำเร็จแทน
+/// โดยไม่ส่งคำขอใหม่ตามระยะเวลา `failure_backoff` (ผู้เรียกจึงไม่ต้องรอ timeout ทุกครั้งระหว่างที่ wepay ล่ม)
+///
+/// การ clone จะใช้ cache ร่วมกัน
+#[derive(Clone)]
+pub struct Catalog {
+    inner: Arc<Inner>,
+}
+
+/// Struct สำหรับสร้าง `Catalog` พร้อมกำหนดระยะเวลา cache และการพักหลังดึงรายการล้มเหลว
+pub struct CatalogBuilder {
+    wepay: Wepay,
+    ttl: Duration,
+    failure_backoff: Duration,
+}
+
+impl CatalogBuilder {
+    /// กำหนดระยะเวลาที่รายการสินค้าจะถูก cache ไว้ (ค่าเริ่มต้น 5 นาที)
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// กำหนดระยะเวลาที่ใช้รายการเดิมต่อโดยไม่ส่งคำขอใหม่ หลังจากการดึงรายการล้มเหลว (ค่าเริ่มต้น 30 วินาที)
+    pub fn failure_backoff(mut self, failure_backoff: Duration) -> Self {
+        self.failure_backoff = failure_backoff;
+        self
+    }
+
+    /// สร้าง `Catalog`
+    pub fn build(self) -> Catalog {
+        Catalog {
+            inner: Arc::new(Inner {
+                wepay: self.wepay,
+                ttl: self.ttl,
+                failure_backoff: self.failure_backoff,
+                state: Mutex::new(State::default()),
+                refreshing: tokio::sync::Mutex::new(()),
+            }),
+        }
+    }
+}
+
+struct Inner {
+    wepay: Wepay,
+    ttl: Duration,
+    failure_backoff: Duration,
+    state: Mutex<State>,
+    refreshing: tokio::sync::Mutex<()>, // ป้องกันการดึงรายการซ้อนกันหลาย task
+}
+
+#[derive(Default)]
+struct State {
+    cached: Option<Cached>,
+    fetched_at: Option<Instant>,
+    retry_at: Option<Instant>, // ดึงรายการล้มเหลวล่าสุด ห้ามดึงใหม่ก่อนเวลานี้
+}
+
+struct Cached {
+    index: Arc<ProductIndex>,
+    validators: CacheValidators,
+}
+
+/// ค่า `ETag`/`Last-Modified` ของรายการที่ cache ไว้ สำหรับส่งคำขอแบบมีเงื่อนไข
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CacheValidators {
+    pub(crate) etag: Option<String>,
+    pub(crate) last_modified: Option<String>,
+}
+
+impl CacheValidators {
+    /// อ่านค่าจาก header ของ response
+    pub(crate) fn from_response(response: &HttpResponse) -> Self {
+        let header = |name| response.header(name).map(str::to_string);
+        CacheValidators {
+            etag: header(ETAG.as_str()),
+            last_modified: header(LAST_MODIFIED.as_str()),
+        }
+    }
+}
+
+impl Catalog {
+    /// เรียกใช้งาน builder เพื่อสร้าง cache ของรายการสินค้า
+    pub fn builder(wepay: Wepay) -> CatalogBuilder {
+        CatalogBuilder {
+            wepay,
+            ttl: DEFAULT_TTL,
+            failure_backoff: DEFAULT_FAILURE_BACKOFF,
+        }
+    }
+
+    /// สร้าง cache ของรายการสินค้าที่หมดอายุหลังจาก `ttl` (ใช้ค่าเริ่มต้นของ `CatalogBuilder` สำหรับค่าอื่น)
+    pub fn new(wepay: Wepay, ttl: Duration) -> Self {
+        Self::builder(wepay).ttl(ttl).build()
+    }
+
+    /// รายการสินค้า (ถ้า cache หมดอายุจะดึงใหม่ก่อน)
+    ///
+    /// ถ้าดึงใหม่ไม่สำเร็จจะคืนรายการล่าสุดที่ดึงสำเร็จ และคืน error เฉพาะเมื่อยังไม่เคยดึงสำเร็จเลย
+    pub async fn get(&self) -> Result<Arc<Product>, WepayError> {
+        Ok(self.index().await?.product().clone())
+    }
+
+    /// ดัชนีของรายการสินค้าสำหรับค้นหาบริษัท (หมดอายุและ fallback เช่นเดียวกับ `get`)
+    pub async fn index(&self) -> Result<Arc<ProductIndex>, WepayError> {
+        if let Some(index) = self.usable() {
+            return Ok(index);
+        }
+
+        let _guard = self.inner.refreshing.lock().await;
+
+        // task อื่นอาจ refresh เสร็จ (หรือล้มเหลว) แล้วระหว่างที่รออยู่
+        if let Some(index) = self.usable() {
+            return Ok(index);
+        }
+
+        match self.fetch().await {
+            Ok(index) => Ok(index),
+            Err(e) => self.cached_index().ok_or(e),
+        }
+    }
+
+    /// คำนวณค่าธรรมเนียมและยอดรวมของคำสั่งซื้อก่อนส่ง (ดู `ProductIndex::quote`)
+    pub async fn quote<R: OrderRequest>(&self, request: &R) -> Result<Quote, WepayError> {
+        self.index().await?.quote(request)
+    }
+
+    /// ดึงรายการสินค้าใหม่ทันทีโดยไม่สนใจ TTL (คืน error ถ้าดึงไม่สำเร็จ แต่ cache เดิมยังคงอยู่)
+    pub async fn refresh(&self) -> Result<Arc<Product>, WepayError> {
+        let _guard = self.inner.refreshing.lock().await;
+        Ok(self.fetch().await?.product().clone())
+    }
+
+    /// รายการสินค้าล่าสุดที่ดึงสำเร็จ (อาจหมดอายุแล้ว) โดยไม่ส่งคำขอ
+    pub fn cached(&self) -> Option<Arc<Product>> {
+        self.cached_index().map(|index| index.product().clone())
+    }
+
+    /// ดัชนีของรายการสินค้าล่าสุดที่ดึงสำเร็จ (อาจหมดอายุแล้ว) โดยไม่ส่งคำขอ
+    pub fn cached_index(&self) -> Option<Arc<ProductIndex>> {
+        let state = self.inner.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.cached.as_ref().map(|c| c.index.clone())
+    }
+
+    /// เริ่ม task เบื้องหลังที่ refresh รายการทันทีและทุก ๆ TTL
+    ///
+    /// ข้อผิดพลาดระหว่าง refresh จะถูกข้ามไปและยังคงใช้รายการเดิม task จะหยุดเองเมื่อ `Catalog` ทุกตัวถูก drop
+    pub fn spawn_refresh(&self) -> JoinHandle<()> {
+        let inner = Arc::downgrade(&self.inner);
+        let ttl = self.inner.ttl;
+
+        tokio::spawn(async move {
+            while let Some(inner) = inner.upgrade() {
+                let _ = Catalog { inner }.refresh().await;
+                tokio::time::sleep(ttl).await;
+            }
+        })
+    }
+
+    /// ฟังก์ชันภายในสำหรับคืนรายการที่ใช้ได้โดยไม่ต้องดึงใหม่
+    /// (ยังไม่หมดอายุ หรือหมดอายุแต่อยู่ในช่วงพักหลังการดึงรายการล้มเหลว)
+    fn usable(&self) -> Option<Arc<ProductIndex>> {
+        let state = self.inner.state.lock().unwrap_or_else(|e| e.into_inner());
+        let fresh = state
+            .fetched_at
+            .is_some_and(|fetched_at| fetched_at.elapsed() < self.inner.ttl);
+        let backing_off = state
+            .retry_at
+            .is_some_and(|retry_at| Instant::now() < retry_at);
+        if fresh || backing_off {
+            state.cached.as_ref().map(|c| c.index.clone())
+        } else {
+            None
+        }
+    }
+
+    /// ฟังก์ชันภายในสำหรับดึงรายการจาก API (แบบมีเงื่อนไขถ้ามีรายการเดิม) และบันทึกลง cache
+    async fn fetch(&self) -> Result<Arc<ProductIndex>, WepayError> {
+        let previous = {
+            let state = self.inner.state.lock().unwrap_or_else(|e| e.into_inner());
+            state
+                .cached
+                .as_ref()
+                .map(|c| (c.index.clone(), c.validators.clone()))
+        };
+
+        let fetched = self
+            .inner
+            .wepay
+            .fetch_product(previous.as_ref().map(|(_, validators)| validators))
+            .await;
+
+        let mut state = self.inner.state.lock().unwrap_or_else(|e| e.into_inner());
+        let fetched = match fetched {
+            Ok(fetched) => fetched,
+            Err(e) => {
+                state.retry_at = Some(Instant::now() + self.inner.failure_backoff);
+                return Err(e);
+            }
+        };
+        state.fetched_at = Some(Instant::now());
+        state.retry_at = None;
+        match (fetched, previous) {
+            (Some((product, validators)), _) => {
+                let index = Arc::new(ProductIndex::new(product));
+                state.cached = Some(Cached {
+                    index: index.clone(),
+                    validators,
+                });
+                Ok(index)
+            }
+            // 304 Not Modified ใช้รายการเดิมต่อ
+            (None, Some((index, _))) => Ok(index),
+            (None, None) => unreachable!("not modified is only returned for conditional requests"),
+        }
+    }
+}
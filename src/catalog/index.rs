@@ -0,0 +1,487 @@
+use super::Quote;
+use crate::error::{Rule, ValidationError, WepayError};
+use crate::money::Baht;
+use crate::request::OrderRequest;
+use crate::response::{Billpay, Cashcard, CompanyKey, Denomination, Gtopup, Mtopup, Product};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+/// ประเภทสินค้าในรายการของ wepay (ตรงกับ `type` ของคำสั่งซื้อ)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    Mtopup,   // เติมเงินมือถือ
+    Cashcard, // บัตรเงินสด
+    Gtopup,   // เติมเงินเกม
+    Billpay,  // ชำระบิล
+}
+
+impl Category {
+    /// ประเภทสินค้าทั้งหมดตามลำดับใน `comp_export.php`
+    pub const ALL: [Category; 4] = [
+        Category::Mtopup,
+        Category::Cashcard,
+        Category::Gtopup,
+        Category::Billpay,
+    ];
+
+    /// แปลงประเภทสินค้าเป็นค่าที่ wepay ใช้
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Category::Mtopup => "mtopup",
+            Category::Cashcard => "cashcard",
+            Category::Gtopup => "gtopup",
+            Category::Billpay => "billpay",
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// บริษัทหนึ่งรายการจาก `Product` ไม่ว่าจะอยู่ในประเภทสินค้าใด
+#[derive(Debug, Clone, Copy)]
+pub enum Company<'a> {
+    Mtopup(&'a Mtopup),
+    Cashcard(&'a Cashcard),
+    Gtopup(&'a Gtopup),
+    Billpay(&'a Billpay),
+}
+
+impl<'a> Company<'a> {
+    /// ประเภทสินค้าของบริษัท
+    pub fn category(&self) -> Category {
+        match self {
+            Company::Mtopup(_) => Category::Mtopup,
+            Company::Cashcard(_) => Category::Cashcard,
+            Company::Gtopup(_) => Category::Gtopup,
+            Company::Billpay(_) => Category::Billpay,
+        }
+    }
+
+    /// รหัสบริษัทที่ normalize แล้ว
+    pub fn key(&self) -> CompanyKey {
+        match self {
+            Company::Mtopup(c) => CompanyKey::from(&c.company_id),
+            Company::Cashcard(c) => CompanyKey::from(&c.company_id),
+            Company::Gtopup(c) => CompanyKey::from(&c.company_id),
+            Company::Billpay(c) => c.company_id.key(),
+        }
+    }
+
+    /// ชื่อบริษัท
+    pub fn name(&self) -> &'a str {
+        match self {
+            Company::Mtopup(c) => &c.company_name,
+            Company::Cashcard(c) => &c.company_name,
+            Company::Gtopup(c) => &c.company_name,
+            Company::Billpay(c) => &c.company_name,
+        }
+    }
+
+    /// ค่าธรรมเนียมต่อรายการ
+    pub fn fee(&self) -> Baht {
+        match self {
+            Company::Mtopup(c) => c.fee,
+            Company::Cashcard(c) => c.fee,
+            Company::Gtopup(c) => c.fee,
+            Company::Billpay(c) => c.fee,
+        }
+    }
+
+    /// ราคาที่บริษัทกำหนด (ว่างเปล่าถ้าบริษัทรับจำนวนเงินใดก็ได้ในช่วงที่กำหนด)
+    pub fn denominations(&self) -> &'a [Denomination] {
+        match self {
+            Company::Mtopup(c) => &c.denomination,
+            Company::Cashcard(c) => &c.denomination,
+            Company::Gtopup(c) => &c.denomination,
+            Company::Billpay(_) => &[],
+        }
+    }
+
+    /// ช่วงจำนวนเงินที่บริษัทรับ (`None` สำหรับบริษัทที่ขายตามราคาใน `denomination` เท่านั้น)
+    pub fn amount_range(&self) -> Option<RangeInclusive<Baht>> {
+        match self {
+            Company::Mtopup(c) => Some(c.minimum_amount..=c.maximum_amount),
+            Company::Billpay(c) => Some(c.minimum_amount..=c.maximum_amount),
+            Company::Cashcard(_) | Company::Gtopup(_) => None,
+        }
+    }
+
+    /// ตรวจสอบจำนวนเงินกับช่วงและราคาที่บริษัทกำหนด
+    ///
+    /// มือถือและบิลต้องอยู่ในช่วง `minimum_amount` ถึง `maximum_amount` และทุกประเภทต้องตรงกับราคาใน
+    /// `denomination` ถ้ามีการกำหนด (บัตรเงินสดและเกมที่ไม่มีราคาในรายการจะไม่ถูกตรวจสอบ และให้ wepay เป็นผู้ตัดสิน)
+    pub fn check_amount(&self, amount: Baht) -> Result<(), Rule> {
+        if let Some(range) = self.amount_range()
+            && !range.contains(&amount)
+        {
+            return Err(Rule::OutOfRange {
+                min: *range.start(),
+                max: *range.end(),
+            });
+        }
+
+        let denominations = self.denominations();
+        if !denominations.is_empty() && !denominations.iter().any(|d| d.price == amount) {
+            return Err(Rule::NotDenomination {
+                allowed: denominations.iter().map(|d| d.price).collect(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// ดัชนีของ `Product` สำหรับค้นหาบริษัทจากรหัส ประเภทสินค้า หรือชื่อ โดยไม่ต้องวนหาเอง
+#[derive(Debug, Clone)]
+pub struct ProductIndex {
+    product: Arc<Product>,
+    companies: HashMap<(Category, CompanyKey), usize>,
+}
+
+impl ProductIndex {
+    /// สร้างดัชนีจากรายการสินค้า
+    ///
+    /// ถ้ามีรหัสบริษัทซ้ำในประเภทเดียวกันจะใช้รายการแรก
+    pub fn new(product: impl Into<Arc<Product>>) -> Self {
+        let product = product.into();
+        let mut index = ProductIndex {
+            product,
+            companies: HashMap::new(),
+        };
+
+        let mut companies = HashMap::new();
+        for category in Category::ALL {
+            for (i, company) in index.category(category).enumerate() {
+                companies.entry((category, company.key())).or_insert(i);
+            }
+        }
+        index.companies = companies;
+        index
+    }
+
+    /// รายการสินค้าต้นฉบับ
+    pub fn product(&self) -> &Arc<Product> {
+        &self.product
+    }
+
+    /// ค้นหาบริษัทจากประเภทสินค้าและรหัสบริษัท
+    pub fn get(&self, category: Category, id: impl Into<CompanyKey>) -> Option<Company<'_>> {
+        let i = *self.companies.get(&(category, id.into()))?;
+        self.nth(category, i)
+    }
+
+    /// ค้นหาบริษัทจากรหัสในทุกประเภทสินค้า (บริษัทเดียวกันอาจมีทั้ง `mtopup` และ `cashcard`)
+    pub fn find(&self, id: impl Into<CompanyKey>) -> Vec<Company<'_>> {
+        let key = id.into();
+        Category::ALL
+            .into_iter()
+            .filter_map(|category| self.get(category, &key))
+            .collect()
+    }
+
+    /// บริษัททั้งหมดในประเภทสินค้าที่ระบุ ตามลำดับใน `comp_export.php`
+    pub fn category(&self, category: Category) -> Box<dyn Iterator<Item = Company<'_>> + '_> {
+        let data = &self.product.data;
+        match category {
+            Category::Mtopup => Box::new(data.mtopup.iter().map(Company::Mtopup)),
+            Category::Cashcard => Box::new(data.cashcard.iter().map(Company::Cashcard)),
+            Category::Gtopup => Box::new(data.gtopup.iter().map(Company::Gtopup)),
+            Category::Billpay => Box::new(data.billpay.iter().map(Company::Billpay)),
+        }
+    }
+
+    /// ค้นหาบริษัทที่ชื่อหรือรหัสมีข้อความที่ระบุ (ไม่สนใจตัวพิมพ์เล็ก/ใหญ่)
+    pub fn search(&self, query: &str) -> Vec<Company<'_>> {
+        let query = query.trim().to_lowercase();
+        Category::ALL
+            .into_iter()
+            .flat_map(|category| self.category(category))
+            .filter(|company| {
+                company.name().to_lowercase().contains(&query)
+                    || company.key().as_str().to_lowercase().contains(&query)
+            })
+            .collect()
+    }
+
+    /// รายการเติมเงินมือถือจากรหัสบริษัท
+    pub fn mtopup(&self, id: impl Into<CompanyKey>) -> Option<&Mtopup> {
+        match self.get(Category::Mtopup, id)? {
+            Company::Mtopup(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// รายการบัตรเงินสดจากรหัสบริษัท (ใช้กับ `CashcardRequest::builder().company()`)
+    pub fn cashcard(&self, id: impl Into<CompanyKey>) -> Option<&Cashcard> {
+        match self.get(Category::Cashcard, id)? {
+            Company::Cashcard(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// รายการเกมจากรหัสบริษัท (ใช้กับ `GameTopupRequest::builder().company()`)
+    pub fn gtopup(&self, id: impl Into<CompanyKey>) -> Option<&Gtopup> {
+        match self.get(Category::Gtopup, id)? {
+            Company::Gtopup(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// รายการชำระบิลจากรหัสบริษัท (ใช้กับ `BillPaymentRequest::builder().company()`)
+    pub fn billpay(&self, id: impl Into<CompanyKey>) -> Option<&Billpay> {
+        match self.get(Category::Billpay, id)? {
+            Company::Billpay(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// ราคาที่บริษัทกำหนด (`None` ถ้าไม่พบบริษัท)
+    pub fn denominations_for(
+        &self,
+        category: Category,
+        id: impl Into<CompanyKey>,
+    ) -> Option<&[Denomination]> {
+        self.get(category, id).map(|c| c.denominations())
+    }
+
+    /// ช่วงจำนวนเงินที่บริษัทรับ (`None` ถ้าไม่พบบริษัทหรือบริษัทขายตามราคาใน `denomination` เท่านั้น)
+    pub fn amount_range_for(
+        &self,
+        category: Category,
+        id: impl Into<CompanyKey>,
+    ) -> Option<RangeInclusive<Baht>> {
+        self.get(category, id)?.amount_range()
+    }
+
+    /// ตรวจสอบจำนวนเงินของคำสั่งซื้อกับรายการสินค้าก่อนส่งไปยัง wepay
+    ///
+    /// คืน `WepayError::Validation` ที่ระบุช่วงหรือราคาที่อนุญาต ถ้าไม่พบบริษัทหรือจำนวนเงินไม่ถูกต้อง
+    pub fn check_amount(
+        &self,
+        category: Category,
+        id: impl Into<CompanyKey>,
+        amount: Baht,
+    ) -> Result<(), WepayError> {
+        self.checked_company(category, id.into(), amount)
+            .map(|_| ())
+    }
+
+    /// คำนวณค่าธรรมเนียมและยอดรวมของคำสั่งซื้อจากรายการสินค้า
+    ///
+    /// จำนวนเงินจะถูกตรวจสอบเช่นเดียวกับ `check_amount` เพื่อไม่ให้เสนอราคาคำสั่งซื้อที่ wepay จะปฏิเสธ
+    pub fn quote<R: OrderRequest>(&self, request: &R) -> Result<Quote, WepayError> {
+        let company = self.checked_company(
+            request.category(),
+            request.company().into(),
+            request.amount(),
+        )?;
+        Ok(Quote::new(company, request.amount()))
+    }
+
+    /// ฟังก์ชันภายในสำหรับค้นหาบริษัทและตรวจสอบจำนวนเงิน
+    fn checked_company(
+        &self,
+        category: Category,
+        key: CompanyKey,
+        amount: Baht,
+    ) -> Result<Company<'_>, WepayError> {
+        let company = self.get(category, &key).ok_or_else(|| {
+            ValidationError::new("company or pay_to_company", Rule::UnknownCompany)
+                .with_value(key.as_str())
+        })?;
+
+        company.check_amount(amount).map_err(|rule| {
+            ValidationError::new("amount or pay_to_amount", rule).with_value(amount.to_string())
+        })?;
+        Ok(company)
+    }
+
+    /// ฟังก์ชันภายในสำหรับดึงบริษัทลำดับที่ `i` ของประเภทสินค้า
+    fn nth(&self, category: Category, i: usize) -> Option<Company<'_>> {
+        let data = &self.product.data;
+        match category {
+            Category::Mtopup => data.mtopup.get(i).map(Company::Mtopup),
+            Category::Cashcard => data.cashcard.get(i).map(Company::Cashcard),
+            Category::Gtopup => data.gtopup.get(i).map(Company::Gtopup),
+            Category::Billpay => data.billpay.get(i).map(Company::Billpay),
+        }
+    }
+}
+
+impl From<Product> for ProductIndex {
+    fn from(product: Product) -> Self {
+        ProductIndex::new(product)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> ProductIndex {
+        let product: Product = serde_json::from_str(
+            r#"{"data": {
+                "mtopup": [{"company_id": "TRMV", "company_name": "True Move", "fee": 0, "minimum_amount": 5, "maximum_amount": 1000, "refundable": false, "denomination": []}],
+                "cashcard": [{"company_id": "TRMV", "company_name": "True Money Cash Card", "fee": 0, "denomination": [{"price": 50, "description": null}, {"price": 90, "description": null}]}],
+                "gtopup": [{"company_id": "GARENA", "company_name": "Garena Shells", "fee": 0, "denomination": [{"price": 100, "description": null}], "congestion": [], "gameservers": [], "refs_format": {"ref1": null, "ref2": null, "ref3": null}}],
+                "billpay": [{"company_id": 12, "company_name": "การไฟฟ้าส่วนภูมิภาค", "fee": 5, "minimum_amount": 1, "maximum_amount": 50000, "barcode_only": false, "refs": [], "additional_info": ""}]
+            }}"#,
+        )
+        .unwrap();
+        ProductIndex::new(product)
+    }
+
+    #[test]
+    fn test_company_key() {
+        assert_eq!(CompanyKey::new(" trmv "), CompanyKey::new("TRMV"));
+        assert_eq!(
+            CompanyKey::from(&crate::response::CompanyId::Integer(12)),
+            CompanyKey::new("12")
+        );
+    }
+
+    #[test]
+    fn test_get_by_category_and_id() {
+        let index = index();
+        assert_eq!(index.mtopup("trmv").unwrap().company_name, "True Move");
+        assert_eq!(
+            index.cashcard("TRMV").unwrap().company_name,
+            "True Money Cash Card"
+        );
+        // company_id ของ billpay เป็นตัวเลขแต่ค้นหาด้วยสตริงได้
+        assert_eq!(index.billpay("12").unwrap().fee, Baht::from_baht(5));
+        assert!(index.gtopup("TRMV").is_none());
+    }
+
+    #[test]
+    fn test_find_and_category() {
+        let index = index();
+        let found = index.find("TRMV");
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].category(), Category::Mtopup);
+        assert_eq!(found[1].category(), Category::Cashcard);
+
+        assert_eq!(index.category(Category::Gtopup).count(), 1);
+    }
+
+    #[test]
+    fn test_search() {
+        let index = index();
+        let names: Vec<_> = index.search("true").iter().map(|c| c.name()).collect();
+        assert_eq!(names, ["True Move", "True Money Cash Card"]);
+        assert_eq!(index.search("ไฟฟ้า")[0].key(), CompanyKey::new("12"));
+        assert_eq!(index.search("garena")[0].category(), Category::Gtopup);
+    }
+
+    #[test]
+    fn test_denominations_and_amount_range() {
+        let index = index();
+        let prices: Vec<_> = index
+            .denominations_for(Category::Cashcard, "TRMV")
+            .unwrap()
+            .iter()
+            .map(|d| d.price)
+            .collect();
+        assert_eq!(prices, [Baht::from_baht(50), Baht::from_baht(90)]);
+
+        assert_eq!(
+            index.amount_range_for(Category::Mtopup, "TRMV"),
+            Some(Baht::from_baht(5)..=Baht::from_baht(1000))
+        );
+        assert_eq!(index.amount_range_for(Category::Cashcard, "TRMV"), None);
+        assert_eq!(index.amount_range_for(Category::Billpay, "99"), None);
+    }
+
+    #[test]
+    fn test_check_amount() {
+        let index = index();
+        assert!(
+            index
+                .check_amount(Category::Mtopup, "TRMV", Baht::from_baht(50))
+                .is_ok()
+        );
+        assert!(
+            index
+                .check_amount(Category::Cashcard, "TRMV", Baht::from_baht(90))
+                .is_ok()
+        );
+
+        let error = index
+            .check_amount(Category::Mtopup, "TRMV", Baht::from_baht(1001))
+            .unwrap_err();
+        assert_eq!(
+            error
+                .validation()
+                .unwrap()
+                .get("amount or pay_to_amount")
+                .unwrap()
+                .rule,
+            Rule::OutOfRange {
+                min: Baht::from_baht(5),
+                max: Baht::from_baht(1000),
+            }
+        );
+
+        let error = index
+            .check_amount(Category::Cashcard, "TRMV", Baht::from_baht(100))
+            .unwrap_err();
+        assert_eq!(
+            error
+                .validation()
+                .unwrap()
+                .get("amount or pay_to_amount")
+                .unwrap()
+                .rule,
+            Rule::NotDenomination {
+                allowed: vec![Baht::from_baht(50), Baht::from_baht(90)],
+            }
+        );
+
+        let error = index
+            .check_amount(Category::Gtopup, "TRMV", Baht::from_baht(100))
+            .unwrap_err();
+        assert_eq!(
+            error
+                .validation()
+                .unwrap()
+                .get("company or pay_to_company")
+                .unwrap()
+                .rule,
+            Rule::UnknownCompany
+        );
+    }
+
+    #[test]
+    fn test_check_amount_without_denominations() {
+        // รายการสินค้าจริงมีบัตรเงินสดและเกมที่ไม่ได้ระบุราคาไว้
+        let product: Product = serde_json::from_str(
+            r#"{"data": {
+                "mtopup": [],
+                "cashcard": [{"company_id": "ONE2CALL", "company_name": "One-2-Call Cash Card", "fee": 0, "denomination": []}],
+                "gtopup": [{"company_id": "ROV", "company_name": "RoV Vouchers", "fee": 0, "denomination": [], "congestion": [], "gameservers": [], "refs_format": {"ref1": null, "ref2": null, "ref3": null}}],
+                "billpay": []
+            }}"#,
+        )
+        .unwrap();
+        let index = ProductIndex::new(product);
+
+        assert!(
+            index
+                .check_amount(Category::Cashcard, "ONE2CALL", Baht::from_baht(300))
+                .is_ok()
+        );
+        assert!(
+            index
+                .check_amount(Category::Gtopup, "ROV", Baht::from_baht(35))
+                .is_ok()
+        );
+    }
+}
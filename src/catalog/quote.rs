@@ -0,0 +1,71 @@
+use super::{Category, Company};
+use crate::money::Baht;
+use crate::response::Balance;
+use serde::Serialize;
+
+/// ค่าใช้จ่ายของคำสั่งซื้อที่คำนวณจากรายการสินค้า (ก่อนส่งไปยัง wepay)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Quote {
+    pub category: Category, // ประเภทสินค้า
+    pub company: String,    // รหัสบริษัท
+    pub amount: Baht,       // จำนวนเงินของคำสั่งซื้อ
+    pub fee: Baht,          // ค่าธรรมเนียมตามรายการสินค้า
+    pub total: Baht,        // ยอดที่จะถูกหักจากบัญชี (amount + fee)
+}
+
+impl Quote {
+    /// ฟังก์ชันภายในสำหรับคำนวณค่าใช้จ่ายจากบริษัทในรายการสินค้า
+    pub(crate) fn new(company: Company<'_>, amount: Baht) -> Self {
+        let fee = company.fee();
+        Quote {
+            category: company.category(),
+            company: company.key().to_string(),
+            amount,
+            fee,
+            total: amount.saturating_add(fee),
+        }
+    }
+
+    /// ตรวจสอบว่ายอดเงินที่ใช้ได้ (`available_balance`) เพียงพอสำหรับคำสั่งซื้อนี้หรือไม่
+    pub fn is_affordable(&self, balance: &Balance) -> bool {
+        self.shortfall(balance).is_none()
+    }
+
+    /// จำนวนเงินที่ขาดถ้ายอดเงินที่ใช้ได้ไม่เพียงพอ
+    pub fn shortfall(&self, balance: &Balance) -> Option<Baht> {
+        (self.total > balance.available_balance)
+            .then(|| self.total.saturating_sub(balance.available_balance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance(available: Baht) -> Balance {
+        Balance {
+            code: "00000".to_string(),
+            ledger_balance: available,
+            available_balance: available,
+        }
+    }
+
+    #[test]
+    fn test_shortfall() {
+        let quote = Quote {
+            category: Category::Billpay,
+            company: "12".to_string(),
+            amount: Baht::from_baht(100),
+            fee: Baht::from_baht(5),
+            total: Baht::from_baht(105),
+        };
+
+        assert!(quote.is_affordable(&balance(Baht::from_baht(105))));
+        assert_eq!(quote.shortfall(&balance(Baht::from_baht(105))), None);
+        assert_eq!(
+            quote.shortfall(&balance(Baht::from_baht(100))),
+            Some(Baht::from_baht(5))
+        );
+        assert!(!quote.is_affordable(&balance(Baht::ZERO)));
+    }
+}
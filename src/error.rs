@@ -1,3 +1,4 @@
+use crate::money::Baht;
 use std::fmt;
 use thiserror::Error;
 
@@ -7,22 +8,288 @@ pub enum WepayError {
     #[error("ไม่มีข้อมูลรับรอง: {0}")]
     MissingCredentials(&'static str), // ขาดข้อมูลรับรองที่จำเป็น
 
-    #[error("รูปแบบข้อมูลไม่ถูกต้อง: {0}")]
-    InvalidFormat(&'static str), // รูปแบบข้อมูลไม่ถูกต้อง (ดูตามเอกสารของ wepay)
+    #[error("ข้อมูลไม่ถูกต้อง: {0}")]
+    Validation(ValidationErrors), // ข้อมูลไม่ผ่านการตรวจสอบ (รวมทุกข้อที่ไม่ผ่านของคำสั่งนั้น)
+
+    #[error("ยอดเงินไม่เพียงพอ: ต้องการ {required} ใช้ได้ {available}")]
+    InsufficientBalance {
+        // ยอดเงินที่ใช้ได้ไม่พอสำหรับคำสั่งซื้อ (ตรวจสอบก่อนส่งโดย `BalanceGuard`)
+        required: Baht,
+        available: Baht,
+    },
+
+    #[error("ข้อมูล callback ไม่ถูกต้อง: {0}")]
+    InvalidCallback(&'static str), // ข้อมูลที่ wepay ส่งกลับมาทาง resp_url ไม่ถูกต้อง
 
     #[error("ข้อผิดพลาดในการร้องขอ: {0}")]
     RequestError(#[from] reqwest::Error), // ข้อผิดพลาดจาก reqwest
 
+    #[error("ข้อผิดพลาดจาก transport: {0}")]
+    Transport(Box<dyn std::error::Error + Send + Sync>), // ข้อผิดพลาดจาก transport อื่นที่ไม่ใช่ reqwest (เช่น ไม่พบคำขอใน cassette)
+
     #[error("ข้อผิดพลาดจาก API: code={code}, desc={desc:?}")]
     ApiError {
         // ข้อผิดพลาดจาก API ตามรหัสสถานะ
         code: StatusCode,
         desc: String,
     },
+
+    #[error("ข้อผิดพลาด HTTP {status}: {body:?}")]
+    HttpError {
+        // HTTP status ไม่สำเร็จ (รวมรหัสสถานะถ้า body เป็น error ของ wepay และตัวอย่าง body)
+        status: u16,
+        code: Option<StatusCode>,
+        body: String,
+    },
+
+    #[error("response ไม่ถูกต้อง (HTTP {status}): {source}, body={body:?}")]
+    InvalidResponse {
+        // HTTP status สำเร็จแต่ body ไม่ตรงกับรูปแบบที่คาดไว้ (เช่น หน้า HTML)
+        status: u16,
+        body: String,
+        source: serde_json::Error,
+    },
+}
+
+/// ความยาวสูงสุดของตัวอย่าง body ที่แนบไปกับข้อผิดพลาด (จำนวนตัวอักษร)
+const BODY_SNIPPET_LEN: usize = 512;
+
+impl WepayError {
+    /// รายการข้อมูลที่ไม่ผ่านการตรวจสอบ (ถ้าเป็น `WepayError::Validation`)
+    pub fn validation(&self) -> Option<&ValidationErrors> {
+        match self {
+            WepayError::Validation(errors) => Some(errors),
+            _ => None,
+        }
+    }
+
+    /// รหัสสถานะจาก wepay (ถ้ามี)
+    pub fn code(&self) -> Option<&StatusCode> {
+        match self {
+            WepayError::ApiError { code, .. } => Some(code),
+            WepayError::HttpError { code, .. } => code.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// HTTP status ของ response (ถ้ามี)
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            WepayError::RequestError(e) => e.status().map(|s| s.as_u16()),
+            WepayError::HttpError { status, .. } | WepayError::InvalidResponse { status, .. } => {
+                Some(*status)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// ฟังก์ชันภายในสำหรับตัด body ให้สั้นพอที่จะแนบไปกับข้อผิดพลาด
+pub(crate) fn snippet(body: &str) -> String {
+    let body = body.trim();
+    match body.char_indices().nth(BODY_SNIPPET_LEN) {
+        Some((end, _)) => format!("{}…", &body[..end]),
+        None => body.to_string(),
+    }
+}
+
+impl From<ValidationError> for WepayError {
+    fn from(error: ValidationError) -> Self {
+        WepayError::Validation(ValidationErrors(vec![error]))
+    }
+}
+
+impl From<ValidationErrors> for WepayError {
+    fn from(errors: ValidationErrors) -> Self {
+        WepayError::Validation(errors)
+    }
+}
+
+/// กฎที่ข้อมูลไม่ผ่านการตรวจสอบ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    Missing,                                // ไม่ได้ระบุข้อมูลที่จำเป็น
+    Empty,                                  // ข้อมูลว่างเปล่า
+    TooLong { max: usize },                 // ยาวเกินจำนวนตัวอักษรที่กำหนด
+    InvalidCharacter(char),                 // มีอักขระที่ไม่อนุญาต
+    BadLength { expected: usize },          // จำนวนหลักไม่ถูกต้อง
+    BadPrefix,                              // ขึ้นต้นไม่ถูกต้อง (เช่น เบอร์มือถือที่ไม่ใช่ 06, 08, 09)
+    NotDigits,                              // ต้องเป็นตัวเลขเท่านั้น
+    NotUrl,                                 // ไม่ใช่ URL ที่ถูกต้อง
+    UnsupportedScheme(String),              // URL ต้องเป็น http หรือ https
+    NotHttps,                               // URL ต้องเป็น https (http ใช้ได้เฉพาะ loopback)
+    NotAmount,                              // ไม่ใช่จำนวนเงิน
+    NotPositive,                            // จำนวนเงินต้องมากกว่าศูนย์
+    TooManyDecimals,                        // ทศนิยมเกิน 2 ตำแหน่ง (เศษสตางค์)
+    OutOfRange { min: Baht, max: Baht },    // จำนวนเงินอยู่นอกช่วงที่บริษัทกำหนด
+    NotDenomination { allowed: Vec<Baht> }, // ราคาไม่ตรงกับราคาที่บริษัทกำหนด
+    PatternMismatch { pattern: String },    // ไม่ตรงกับรูปแบบ (`refs_format`) ที่กำหนด
+    InvalidPattern { pattern: String },     // รูปแบบ (`refs_format`) จาก wepay ไม่ใช่ regex ที่ถูกต้อง
+    NotAllowed { allowed: Vec<String> },    // ไม่อยู่ในรายการที่อนุญาต (เช่น `gameservers`)
+    UnknownCompany,                         // ไม่พบบริษัทในรายการสินค้า
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::Missing => write!(f, "ไม่ได้ระบุข้อมูล"),
+            Rule::Empty => write!(f, "ข้อมูลว่างเปล่า"),
+            Rule::TooLong { max } => write!(f, "ยาวเกิน {} ตัวอักษร", max),
+            Rule::InvalidCharacter(c) => write!(f, "มีอักขระที่ไม่อนุญาต {:?}", c),
+            Rule::BadLength { expected } => write!(f, "ต้องมี {} หลัก", expected),
+            Rule::BadPrefix => write!(f, "ขึ้นต้นไม่ถูกต้อง"),
+            Rule::NotDigits => write!(f, "ต้องเป็นตัวเลขเท่านั้น"),
+            Rule::NotUrl => write!(f, "ไม่ใช่ URL ที่ถูกต้อง"),
+            Rule::UnsupportedScheme(scheme) => {
+                write!(f, "ต้องเป็น http หรือ https (ได้รับ {})", scheme)
+            }
+            Rule::NotHttps => write!(f, "ต้องเป็น https"),
+            Rule::NotAmount => write!(f, "ไม่ใช่จำนวนเงิน"),
+            Rule::NotPositive => write!(f, "ต้องมากกว่าศูนย์"),
+            Rule::TooManyDecimals => write!(f, "ทศนิยมเกิน 2 ตำแหน่ง"),
+            Rule::OutOfRange { min, max } => write!(f, "ต้องอยู่ระหว่าง {} ถึง {}", min, max),
+            Rule::NotDenomination { allowed } => write!(f, "ต้องเป็นหนึ่งใน {}", join(allowed)),
+            Rule::PatternMismatch { pattern } => write!(f, "ไม่ตรงกับรูปแบบ {}", pattern),
+            Rule::InvalidPattern { pattern } => write!(f, "รูปแบบ {} ไม่ถูกต้อง", pattern),
+            Rule::NotAllowed { allowed } => write!(f, "ต้องเป็นหนึ่งใน {}", join(allowed)),
+            Rule::UnknownCompany => write!(f, "ไม่พบในรายการสินค้า"),
+        }
+    }
+}
+
+/// ฟังก์ชันภายในสำหรับแสดงรายการค่าที่อนุญาต
+fn join<T: fmt::Display>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// ข้อมูลหนึ่งรายการที่ไม่ผ่านการตรวจสอบ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    pub field: &'static str,   // ชื่อข้อมูล (ตามชื่อ parameter ของ wepay)
+    pub value: Option<String>, // ค่าที่ได้รับ (ข้อมูลส่วนตัวจะถูกปิดบังไว้)
+    pub rule: Rule,            // กฎที่ไม่ผ่าน
+}
+
+impl ValidationError {
+    /// สร้างข้อผิดพลาดโดยไม่แนบค่าที่ได้รับ
+    pub fn new(field: &'static str, rule: Rule) -> Self {
+        ValidationError {
+            field,
+            value: None,
+            rule,
+        }
+    }
+
+    /// แนบค่าที่ได้รับตามจริง
+    pub fn with_value(mut self, value: impl Into<String>) -> Self {
+        self.value = Some(value.into());
+        self
+    }
+
+    /// แนบค่าที่ได้รับโดยปิดบังทุกตัวอักษรยกเว้น 4 ตัวสุดท้าย (เช่น เบอร์มือถือ หรือเลขบัตรเครดิต)
+    pub fn with_redacted_value(mut self, value: &str) -> Self {
+        let count = value.chars().count();
+        let keep = if count > 4 { 4 } else { 0 };
+        let redacted = value
+            .chars()
+            .enumerate()
+            .map(|(i, c)| if i < count - keep { '*' } else { c })
+            .collect();
+        self.value = Some(redacted);
+        self
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.field, self.rule)?;
+        if let Some(value) = &self.value {
+            write!(f, " (ได้รับ {:?})", value)?;
+        }
+        Ok(())
+    }
+}
+
+/// รายการข้อมูลทั้งหมดที่ไม่ผ่านการตรวจสอบของคำสั่งหนึ่ง
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationErrors(Vec<ValidationError>);
+
+impl ValidationErrors {
+    /// จำนวนข้อมูลที่ไม่ผ่านการตรวจสอบ
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// ตรวจสอบว่าไม่มีข้อผิดพลาดหรือไม่
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// วนลูปข้อผิดพลาดทั้งหมดตามลำดับที่ตรวจพบ
+    pub fn iter(&self) -> std::slice::Iter<'_, ValidationError> {
+        self.0.iter()
+    }
+
+    /// ค้นหาข้อผิดพลาดของข้อมูลที่ระบุ
+    pub fn get(&self, field: &str) -> Option<&ValidationError> {
+        self.0.iter().find(|e| e.field == field)
+    }
+
+    /// แปลงเป็น `Vec<ValidationError>`
+    pub fn into_vec(self) -> Vec<ValidationError> {
+        self.0
+    }
+
+    /// เพิ่มข้อผิดพลาด
+    pub(crate) fn push(&mut self, error: ValidationError) {
+        self.0.push(error);
+    }
+
+    /// คืนค่าที่ระบุ หรือบันทึกว่าไม่ได้ระบุข้อมูลที่จำเป็น
+    pub(crate) fn require<T>(&mut self, field: &'static str, value: Option<T>) -> Option<T> {
+        if value.is_none() {
+            self.push(ValidationError::new(field, Rule::Missing));
+        }
+        value
+    }
+
+    /// คืนค่าที่แปลงสำเร็จ หรือบันทึกข้อผิดพลาดจากการแปลง (หรือว่าไม่ได้ระบุข้อมูล)
+    pub(crate) fn require_valid<T>(
+        &mut self,
+        field: &'static str,
+        value: Option<Result<T, ValidationError>>,
+    ) -> Option<T> {
+        match self.require(field, value)? {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.push(error);
+                None
+            }
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&join(&self.0))
+    }
+}
+
+impl<'a> IntoIterator for &'a ValidationErrors {
+    type Item = &'a ValidationError;
+    type IntoIter = std::slice::Iter<'a, ValidationError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
 }
 
 /// รหัสสถานะจาก API
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum StatusCode {
     Success,                      // รายการสำเร็จ
     InternalErrorDb,              // Internal Error (ไม่สามารถเชื่อมต่อระบบฐานข้อมูลได้)
@@ -130,3 +397,18 @@ impl StatusCode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet() {
+        assert_eq!(snippet("  <html></html>\n"), "<html></html>");
+
+        let long = "ก".repeat(BODY_SNIPPET_LEN + 10);
+        let short = snippet(&long);
+        assert_eq!(short.chars().count(), BODY_SNIPPET_LEN + 1);
+        assert!(short.ends_with('…'));
+    }
+}
@@ -0,0 +1,329 @@
+use crate::error::{Rule, ValidationError, WepayError};
+use crate::response::{BillCommon, Transaction};
+use crate::types::DestRef;
+use crate::validator;
+use async_trait::async_trait;
+use rand::Rng;
+use rand::distr::Alphanumeric;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// ความยาวสูงสุดของ dest_ref (อ้างอิงจาก doc ของ wepay)
+const DEST_REF_LEN: usize = 20;
+
+/// ความยาวสูงสุดของ prefix เพื่อให้เหลือพื้นที่สำหรับเวลาและค่าสุ่ม
+const MAX_PREFIX_LEN: usize = 8;
+
+/// ตัวสร้าง dest_ref ที่ไม่ซ้ำกัน (prefix + เวลาปัจจุบันแบบ base36 + อักขระสุ่ม รวม 20 ตัวอักษร)
+///
+/// ค่าเริ่มต้น (`Default`) คือไม่มี prefix
+#[derive(Debug, Clone, Default)]
+pub struct DestRefGenerator {
+    prefix: String,
+}
+
+impl DestRefGenerator {
+    /// สร้างตัวสร้าง dest_ref พร้อม prefix (a-z, A-Z, 0-9 ไม่เกิน 8 ตัวอักษร)
+    pub fn new(prefix: impl Into<String>) -> Result<Self, WepayError> {
+        let prefix = prefix.into();
+        let rule = if prefix.len() > MAX_PREFIX_LEN {
+            Err(Rule::TooLong {
+                max: MAX_PREFIX_LEN,
+            })
+        } else {
+            validator::check_ref(&prefix)
+        };
+        if let Err(rule) = rule {
+            return Err(ValidationError::new("dest_ref prefix", rule)
+                .with_value(prefix)
+                .into());
+        }
+        Ok(DestRefGenerator { prefix })
+    }
+
+    /// สร้าง dest_ref ใหม่
+    pub fn generate(&self) -> DestRef {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+
+        let mut dest_ref = self.prefix.clone();
+        dest_ref.push_str(&to_base36(millis));
+
+        // เติมอักขระสุ่มจนครบความยาวสูงสุด เพื่อไม่ให้ซ้ำกันแม้สร้างในมิลลิวินาทีเดียวกัน
+        let remaining = DEST_REF_LEN.saturating_sub(dest_ref.len());
+        dest_ref.extend(
+            rand::rng()
+                .sample_iter(Alphanumeric)
+                .take(remaining)
+                .map(char::from),
+        );
+        dest_ref.truncate(DEST_REF_LEN);
+
+        // prefix ผ่านการตรวจสอบแล้ว และส่วนที่เหลือเป็น a-z, A-Z, 0-9 เสมอ
+        DestRef::try_from(dest_ref).expect("generated dest_ref is always valid")
+    }
+}
+
+/// แปลงตัวเลขเป็นสตริง base36 ตัวพิมพ์ใหญ่
+fn to_base36(mut n: u128) -> String {
+    const DIGITS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    let mut out = Vec::new();
+    loop {
+        out.push(DIGITS[(n % 36) as usize]);
+        n /= 36;
+        if n == 0 {
+            break;
+        }
+    }
+    out.reverse();
+    String::from_utf8(out).unwrap_or_default()
+}
+
+/// สถานะของคำสั่งซื้อที่บันทึกไว้ใน `OrderStore`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum OrderState {
+    Pending,               // ส่งคำสั่งซื้อแล้วแต่ยังไม่ทราบผล
+    Placed(BillCommon),    // wepay รับคำสั่งซื้อแล้ว
+    Resolved(Transaction), // ทราบผลจากการตรวจสอบสถานะรายการ
+}
+
+/// Trait สำหรับที่เก็บคำสั่งซื้อที่ส่งไปแล้ว (เช่น ฐานข้อมูล) เพื่อป้องกันการส่งคำสั่งซื้อซ้ำ
+///
+/// ควรใช้ที่เก็บแบบถาวรถ้าต้องการป้องกันการส่งซ้ำข้าม process หรือหลังจาก restart
+#[async_trait]
+pub trait OrderStore: Send + Sync {
+    /// ดึงสถานะของคำสั่งซื้อจาก dest_ref
+    async fn load(&self, dest_ref: &str) -> Result<Option<OrderState>, WepayError>;
+
+    /// บันทึก `OrderState::Pending` ถ้ายังไม่มีคำสั่งซื้อของ dest_ref นี้ และคืน `None`
+    /// ถ้ามีอยู่แล้วจะคืนสถานะเดิมโดยไม่แก้ไข
+    ///
+    /// ต้องตรวจสอบและบันทึกในขั้นตอนเดียว (เช่น `INSERT ... ON CONFLICT DO NOTHING`)
+    /// เพื่อไม่ให้ผู้เรียกสองรายได้ `None` พร้อมกันแล้วส่งคำสั่งซื้อซ้ำ
+    async fn reserve(&self, dest_ref: &str) -> Result<Option<OrderState>, WepayError>;
+
+    /// บันทึกสถานะของคำสั่งซื้อ
+    async fn save(&self, dest_ref: &str, state: &OrderState) -> Result<(), WepayError>;
+
+    /// ลบคำสั่งซื้อที่ wepay ปฏิเสธแน่นอนแล้ว
+    async fn remove(&self, dest_ref: &str) -> Result<(), WepayError>;
+}
+
+/// ที่เก็บคำสั่งซื้อในหน่วยความจำ (ค่าเริ่มต้นของ `WepayBuilder`)
+#[derive(Debug, Default)]
+pub struct MemoryOrderStore {
+    orders: Mutex<HashMap<String, OrderState>>,
+}
+
+impl MemoryOrderStore {
+    /// สร้างที่เก็บคำสั่งซื้อเปล่า
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl OrderStore for MemoryOrderStore {
+    async fn load(&self, dest_ref: &str) -> Result<Option<OrderState>, WepayError> {
+        let orders = self.orders.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(orders.get(dest_ref).cloned())
+    }
+
+    async fn reserve(&self, dest_ref: &str) -> Result<Option<OrderState>, WepayError> {
+        let mut orders = self.orders.lock().unwrap_or_else(|e| e.into_inner());
+        match orders.get(dest_ref) {
+            Some(state) => Ok(Some(state.clone())),
+            None => {
+                orders.insert(dest_ref.to_string(), OrderState::Pending);
+                Ok(None)
+            }
+        }
+    }
+
+    async fn save(&self, dest_ref: &str, state: &OrderState) -> Result<(), WepayError> {
+        let mut orders = self.orders.lock().unwrap_or_else(|e| e.into_inner());
+        orders.insert(dest_ref.to_string(), state.clone());
+        Ok(())
+    }
+
+    async fn remove(&self, dest_ref: &str) -> Result<(), WepayError> {
+        let mut orders = self.orders.lock().unwrap_or_else(|e| e.into_inner());
+        orders.remove(dest_ref);
+        Ok(())
+    }
+}
+
+// อนุญาตให้ใช้ที่เก็บเดียวกันร่วมกับส่วนอื่นของระบบได้
+#[async_trait]
+impl<S: OrderStore + ?Sized> OrderStore for Arc<S> {
+    async fn load(&self, dest_ref: &str) -> Result<Option<OrderState>, WepayError> {
+        (**self).load(dest_ref).await
+    }
+
+    async fn reserve(&self, dest_ref: &str) -> Result<Option<OrderState>, WepayError> {
+        (**self).reserve(dest_ref).await
+    }
+
+    async fn save(&self, dest_ref: &str, state: &OrderState) -> Result<(), WepayError> {
+        (**self).save(dest_ref, state).await
+    }
+
+    async fn remove(&self, dest_ref: &str) -> Result<(), WepayError> {
+        (**self).remove(dest_ref).await
+    }
+}
+
+/// ตัวล็อกแยกตาม dest_ref สำหรับให้ `submit_once` ของ dest_ref เดียวกันใน `Wepay` ทำงานทีละรายการ
+#[derive(Default)]
+pub(crate) struct InFlight {
+    locks: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+/// สิทธิ์ในการส่งคำสั่งซื้อของ dest_ref หนึ่ง (ปล่อยเมื่อถูก drop)
+pub(crate) struct InFlightGuard<'a> {
+    owner: &'a InFlight,
+    dest_ref: String,
+    _guard: tokio::sync::OwnedMutexGuard<()>,
+}
+
+impl InFlight {
+    /// รอจนกว่าจะไม่มี `submit_once` อื่นของ dest_ref นี้ทำงานอยู่
+    pub(crate) async fn lock(&self, dest_ref: &str) -> InFlightGuard<'_> {
+        let lock = {
+            let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+            locks.entry(dest_ref.to_string()).or_default().clone()
+        };
+        InFlightGuard {
+            owner: self,
+            dest_ref: dest_ref.to_string(),
+            _guard: lock.lock_owned().await,
+        }
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        // ลบตัวล็อกเมื่อไม่มีผู้รอ (เหลือเฉพาะใน map และใน guard นี้)
+        let mut locks = self.owner.locks.lock().unwrap_or_else(|e| e.into_inner());
+        if locks
+            .get(&self.dest_ref)
+            .is_some_and(|lock| Arc::strong_count(lock) == 2)
+        {
+            locks.remove(&self.dest_ref);
+        }
+    }
+}
+
+/// ผลลัพธ์ของการส่งคำสั่งซื้อผ่าน `Wepay::submit_once`
+#[derive(Debug, Clone)]
+pub enum Submission {
+    Placed(BillCommon),    // คำสั่งซื้อถูกส่งสำเร็จ (ในครั้งนี้หรือครั้งก่อนหน้า)
+    Resolved(Transaction), // พบรายการเดิมของ dest_ref นี้จากการตรวจสอบสถานะ
+}
+
+impl Submission {
+    /// transaction_id ของรายการ
+    pub fn transaction_id(&self) -> &str {
+        match self {
+            Submission::Placed(bill) => &bill.transaction_id,
+            Submission::Resolved(transaction) => &transaction.transaction_id,
+        }
+    }
+}
+
+/// ตรวจสอบว่าข้อผิดพลาดนี้ทำให้ไม่ทราบว่า wepay ได้รับคำสั่งซื้อหรือไม่
+pub(crate) fn is_ambiguous(error: &WepayError) -> bool {
+    match error {
+        // เชื่อมต่อไม่ได้เลย แปลว่าคำขอยังไม่ถึง wepay
+        WepayError::RequestError(e) => !e.is_connect() && !e.is_builder(),
+        // ได้รับ response แต่อ่านไม่ได้ (เช่น หน้า error จาก proxy) จึงไม่ทราบผลของคำสั่งซื้อ
+        WepayError::HttpError { code: None, .. } | WepayError::InvalidResponse { .. } => true,
+        // transport อื่นไม่ได้ระบุว่าคำขอถึง wepay หรือไม่
+        WepayError::Transport(_) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_generate_dest_ref() {
+        let generator = DestRefGenerator::new("ORD").unwrap();
+        let a = generator.generate();
+        let b = generator.generate();
+
+        assert_eq!(a.as_str().len(), DEST_REF_LEN);
+        assert!(a.as_str().starts_with("ORD"));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_generate_without_prefix() {
+        let dest_ref = DestRefGenerator::default().generate();
+        assert_eq!(dest_ref.as_str().len(), DEST_REF_LEN);
+    }
+
+    #[test]
+    fn test_invalid_prefix() {
+        assert!(DestRefGenerator::new("ORDER_").is_err());
+        assert!(DestRefGenerator::new("TOOLONGPREFIX").is_err());
+    }
+
+    #[test]
+    fn test_base36() {
+        assert_eq!(to_base36(0), "0");
+        assert_eq!(to_base36(35), "Z");
+        assert_eq!(to_base36(36), "10");
+    }
+
+    #[tokio::test]
+    async fn test_memory_store() {
+        let store = MemoryOrderStore::new();
+
+        assert!(store.load("A1").await.unwrap().is_none());
+        store.save("A1", &OrderState::Pending).await.unwrap();
+        assert!(matches!(
+            store.load("A1").await.unwrap(),
+            Some(OrderState::Pending)
+        ));
+        store.remove("A1").await.unwrap();
+        assert!(store.load("A1").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_reserve() {
+        let store = MemoryOrderStore::new();
+
+        // ครั้งแรกได้สิทธิ์ส่งคำสั่งซื้อ ครั้งต่อไปได้สถานะเดิม
+        assert!(store.reserve("A1").await.unwrap().is_none());
+        assert!(matches!(
+            store.reserve("A1").await.unwrap(),
+            Some(OrderState::Pending)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_in_flight() {
+        let in_flight = InFlight::default();
+
+        let guard = in_flight.lock("A1").await;
+        // dest_ref อื่นไม่ต้องรอ
+        drop(in_flight.lock("A2").await);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(10), in_flight.lock("A1"))
+                .await
+                .is_err()
+        );
+
+        drop(guard);
+        drop(in_flight.lock("A1").await);
+        assert!(in_flight.locks.lock().unwrap().is_empty());
+    }
+}
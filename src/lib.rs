@@ -1,9 +1,41 @@
+pub mod balance;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod callback;
+pub mod catalog;
 pub mod error;
+pub mod idempotency;
+pub mod money;
+pub mod rate_limit;
+pub mod request;
 pub mod response;
+pub mod retry;
+#[cfg(feature = "test-support")]
+pub mod simulator;
+pub mod transport;
+pub mod types;
 mod validator;
 
-use error::{StatusCode, WepayError};
-use reqwest::{Client, ClientBuilder, Proxy};
+use balance::BalanceGuard;
+use catalog::{CacheValidators, Catalog};
+use error::{Rule, StatusCode, ValidationError, WepayError};
+use idempotency::{InFlight, MemoryOrderStore, OrderState, OrderStore, Submission};
+use money::Baht;
+use rate_limit::RateLimiter;
+use request::{
+    BillPaymentRequest, BillpayRefs, CashcardRequest, GameTopupRequest, GtopupRefs,
+    MobileTopupRequest, OrderRequest, TransactionQuery,
+};
+use reqwest::header::{IF_MODIFIED_SINCE, IF_NONE_MATCH};
+use reqwest::{ClientBuilder, Proxy};
+use retry::RetryPolicy;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use transport::{
+    Cassette, HttpRequest, HttpResponse, Recorder, Replayer, ReqwestTransport, Transport,
+};
+use types::{CallbackUrl, DestRef, MobileNumber};
 
 /// Struct สำหรับสร้างอินสแตนซ์ของ Wepay ด้วยตัวเลือกที่กำหนดเอง
 pub struct WepayBuilder {
@@ -11,14 +43,38 @@ pub struct WepayBuilder {
     password: Option<String>,
     base_url: String,
     proxy: Option<Proxy>,
+    timeout: Option<Duration>,
+    retry: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    order_store: Option<Arc<dyn OrderStore>>,
+    preflight: Option<Catalog>,
+    balance_guard: Option<BalanceGuard>,
+    cassette: Option<CassetteMode>,
+    transport: Option<Arc<dyn Transport>>,
+}
+
+/// การบันทึกหรือเล่นซ้ำคำขอด้วย cassette
+enum CassetteMode {
+    Record(PathBuf),
+    Replay(Cassette),
 }
 
 /// Struct หลักที่ใช้เรียกใช้งาน API ต่าง ๆ ของ Wepay
+///
+/// การ clone จะใช้ connection pool และตัวจำกัดอัตราการส่งคำขอร่วมกัน
+#[derive(Clone)]
 pub struct Wepay {
-    client: Client,
+    transport: Arc<dyn Transport>,
+    recorder: Option<Arc<Recorder>>, // มีเมื่อเปิดใช้งาน `WepayBuilder::record`
     username: Option<String>,
     password: Option<String>,
     base_url: String,
+    retry: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    order_store: Arc<dyn OrderStore>,
+    in_flight: Arc<InFlight>, // submit_once ที่กำลังทำงานแยกตาม dest_ref
+    preflight: Option<Catalog>,
+    balance_guard: Option<BalanceGuard>,
 }
 
 // กำหนดค่าเริ่มต้นของ WepayBuilder
@@ -36,6 +92,14 @@ impl WepayBuilder {
             password: None,
             base_url: "https://www.wepay.in.th".to_string(),
             proxy: None,
+            timeout: None,
+            retry: RetryPolicy::none(),
+            rate_limiter: None,
+            order_store: None,
+            preflight: None,
+            balance_guard: None,
+            cassette: None,
+            transport: None,
         }
     }
 
@@ -64,6 +128,80 @@ impl WepayBuilder {
         self
     }
 
+    /// กำหนดระยะเวลารอสูงสุดของแต่ละคำขอ ตั้งแต่เชื่อมต่อจนอ่าน response ครบ (ค่าเริ่มต้นคือไม่จำกัด)
+    ///
+    /// คำขอที่หมดเวลาจะได้ `WepayError::RequestError` และถ้าใช้ร่วมกับ `retry` เวลาจะนับแยกในแต่ละครั้งที่ส่ง
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// กำหนดนโยบายการส่งคำขอซ้ำเมื่อเกิดข้อผิดพลาดชั่วคราว (ค่าเริ่มต้นคือไม่ส่งซ้ำ)
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// จำกัดอัตราการส่งคำขอ (คำขอต่อวินาที และจำนวนคำขอที่ส่งติดกันได้) เพื่อไม่ให้ถูกปฏิเสธด้วย 10003
+    ///
+    /// # Panics
+    ///
+    /// ถ้า `requests_per_second` ไม่ใช่ค่าบวก
+    pub fn rate_limit(mut self, requests_per_second: f64, burst: u32) -> Self {
+        self.rate_limiter = Some(RateLimiter::new(requests_per_second, burst));
+        self
+    }
+
+    /// กำหนดที่เก็บคำสั่งซื้อสำหรับ `Wepay::submit_once` (ค่าเริ่มต้นคือ `MemoryOrderStore`)
+    pub fn order_store(mut self, store: impl OrderStore + 'static) -> Self {
+        self.order_store = Some(Arc::new(store));
+        self
+    }
+
+    /// ตรวจสอบจำนวนเงินของคำสั่งซื้อกับรายการสินค้าใน `catalog` ก่อนส่งไปยัง wepay (ค่าเริ่มต้นคือไม่ตรวจสอบ)
+    ///
+    /// คำสั่งซื้อที่จำนวนเงินอยู่นอกช่วงหรือไม่ตรงกับราคาที่บริษัทกำหนดจะถูกปฏิเสธด้วย
+    /// `WepayError::Validation` แทน `InvalidPaymentAmount` (30007) และถ้ายังดึงรายการสินค้าไม่ได้เลย
+    /// คำสั่งซื้อจะไม่ถูกส่ง
+    pub fn preflight(mut self, catalog: Catalog) -> Self {
+        self.preflight = Some(catalog);
+        self
+    }
+
+    /// ตรวจสอบยอดเงินก่อนส่งคำสั่งซื้อด้วย `guard` (ค่าเริ่มต้นคือไม่ตรวจสอบ)
+    ///
+    /// ถ้าใช้ร่วมกับ `preflight` จะกันยอดเงินรวมค่าธรรมเนียมจากรายการสินค้า นอกนั้นกันเฉพาะจำนวนเงินของคำสั่งซื้อ
+    pub fn balance_guard(mut self, guard: BalanceGuard) -> Self {
+        self.balance_guard = Some(guard);
+        self
+    }
+
+    /// ส่งคำขอทั้งหมดผ่าน `transport` แทน `ReqwestTransport` (ค่าเริ่มต้น)
+    ///
+    /// เมื่อกำหนดแล้ว `proxy` และ `timeout` จะไม่มีผล ส่วน `record` จะบันทึกคำขอที่ส่งผ่าน `transport` นี้
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// บันทึกคำขอและ response ทุกคู่ลงไฟล์ cassette ที่ `path` เหมาะสำหรับแนบไปกับรายงานปัญหาหรือใช้ในการทดสอบ
+    ///
+    /// username/password จะถูกปิดบัง และ header เช่น `Authorization`/`Set-Cookie` จะถูกตัดออก
+    /// ไฟล์จะถูกเขียนเมื่อเรียก `Wepay::flush_recording` หรือเมื่อ `Wepay` ตัวสุดท้ายถูก drop
+    pub fn record(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cassette = Some(CassetteMode::Record(path.into()));
+        self
+    }
+
+    /// ตอบคำขอจาก `cassette` แทนการเชื่อมต่อกับ wepay
+    ///
+    /// คำขอจะถูกจับคู่ตาม method, endpoint และข้อมูลใน form (ไม่สนใจ base_url และ username/password)
+    /// คำขอที่ไม่พบใน cassette จะได้ `WepayError::Transport`
+    pub fn replay(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(CassetteMode::Replay(cassette));
+        self
+    }
+
     /// สร้าง instance ของ Wepay จากค่า configuration ทั้งหมดที่ตั้งไว้
     pub fn build(self) -> Result<Wepay, WepayError> {
         let mut builder = ClientBuilder::new();
@@ -73,14 +211,38 @@ impl WepayBuilder {
             builder = builder.proxy(proxy);
         }
 
-        // สร้าง reqwest client
-        let client = builder.build()?;
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        // ใช้ transport ที่กำหนดไว้ ถ้าไม่มีให้สร้าง reqwest client
+        let inner = match self.transport {
+            Some(transport) => transport,
+            None => Arc::new(ReqwestTransport::new(builder.build()?)),
+        };
+        let (transport, recorder): (Arc<dyn Transport>, _) = match self.cassette {
+            None => (inner, None),
+            Some(CassetteMode::Record(path)) => {
+                let recorder = Arc::new(Recorder::new(inner, path));
+                (recorder.clone(), Some(recorder))
+            }
+            Some(CassetteMode::Replay(cassette)) => (Arc::new(Replayer::new(cassette)), None),
+        };
 
         Ok(Wepay {
-            client,
+            transport,
+            recorder,
             username: self.username,
             password: self.password,
             base_url: self.base_url,
+            retry: self.retry,
+            rate_limiter: self.rate_limiter,
+            order_store: self
+                .order_store
+                .unwrap_or_else(|| Arc::new(MemoryOrderStore::new())),
+            in_flight: Arc::new(InFlight::default()),
+            preflight: self.preflight,
+            balance_guard: self.balance_guard,
         })
     }
 }
@@ -91,22 +253,61 @@ impl Wepay {
         WepayBuilder::new()
     }
 
-    /// ฟังก์ชันภายในเพื่อจัดการกับ response ของ API และตรวจสอบว่า response สำเร็จหรือไม่
-    async fn handle_response<T: response::ApiResponse + serde::de::DeserializeOwned>(
-        response: reqwest::Response,
-    ) -> Result<T, WepayError> {
-        let status = response.status();
-        if !status.is_success() {
-            // ถ้า HTTP status เป็น error ให้อ่านข้อมูล error และส่งกลับเป็น WepayError
-            let error = response.json::<response::ErrorResponse>().await?;
-            return Err(WepayError::ApiError {
-                code: StatusCode::from_str(&error.code),
-                desc: error.desc,
-            });
+    /// เขียนคำขอและ response ที่บันทึกไว้ลงไฟล์ cassette ทันที (ไม่มีผลถ้าไม่ได้ใช้ `WepayBuilder::record`)
+    ///
+    /// ข้อผิดพลาดในการเขียนไฟล์จะถูกคืนจากฟังก์ชันนี้เท่านั้น และไม่มีผลต่อคำขอที่ส่งไปแล้ว
+    pub async fn flush_recording(&self) -> Result<(), WepayError> {
+        match &self.recorder {
+            Some(recorder) => recorder.flush().await,
+            None => Ok(()),
+        }
+    }
+
+    /// ฟังก์ชันภายในสำหรับดึง username/password ที่กำหนดไว้
+    fn credentials(&self) -> Result<(&str, &str), WepayError> {
+        let username = self
+            .username
+            .as_ref()
+            .ok_or(WepayError::MissingCredentials("username"))?;
+        let password = self
+            .password
+            .as_ref()
+            .ok_or(WepayError::MissingCredentials("password"))?;
+        Ok((username, password))
+    }
+
+    /// ฟังก์ชันภายในสำหรับรอคิวก่อนส่งคำขอ (ถ้ามีการจำกัดอัตราการส่งคำขอ)
+    async fn throttle(&self) {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire().await;
         }
+    }
+
+    /// ฟังก์ชันภายในสำหรับส่ง form request ไปยัง client_api และแปลง response ตามนโยบายการส่งซ้ำ
+    ///
+    /// `idempotent` ระบุว่าคำขอนี้ส่งซ้ำได้อย่างปลอดภัย (false สำหรับคำสั่งซื้อทุกประเภท)
+    async fn post<T: response::ApiResponse + serde::de::DeserializeOwned>(
+        &self,
+        params: &[(&str, &str)],
+        idempotent: bool,
+    ) -> Result<T, WepayError> {
+        let url = format!("{}/client_api.json.php", self.base_url);
+
+        self.retry
+            .run(idempotent, || async {
+                self.throttle().await;
+                let response = self.transport.send(HttpRequest::post(&url, params)).await?;
+                Self::handle_response(response)
+            })
+            .await
+    }
 
+    /// ฟังก์ชันภายในเพื่อจัดการกับ response ของ API และตรวจสอบว่า response สำเร็จหรือไม่
+    fn handle_response<T: response::ApiResponse + serde::de::DeserializeOwned>(
+        response: HttpResponse,
+    ) -> Result<T, WepayError> {
         // แปลงข้อมูล response เป็น GenericApiResponse
-        let res = response.json::<response::GenericApiResponse<T>>().await?;
+        let res = Self::read_json::<response::GenericApiResponse<T>>(response)?;
         match res {
             response::GenericApiResponse::Success(data) => {
                 if data.code() == StatusCode::Success.as_str() {
@@ -125,89 +326,350 @@ impl Wepay {
         }
     }
 
+    /// ฟังก์ชันภายในสำหรับอ่าน body ของ response เป็น JSON
+    ///
+    /// HTTP status ที่ไม่สำเร็จจะคืน `HttpError` (พร้อมรหัสสถานะถ้า body เป็น error ของ wepay)
+    /// และ body ที่ไม่ตรงกับ `T` จะคืน `ApiError` ถ้าเป็น error ของ wepay หรือ `InvalidResponse`
+    fn read_json<T: serde::de::DeserializeOwned>(response: HttpResponse) -> Result<T, WepayError> {
+        let wepay_error = || serde_json::from_str::<response::ErrorResponse>(&response.body).ok();
+
+        if !response.is_success() {
+            return Err(WepayError::HttpError {
+                status: response.status,
+                code: wepay_error().map(|error| StatusCode::from_str(&error.code)),
+                body: error::snippet(&response.body),
+            });
+        }
+
+        serde_json::from_str(&response.body).map_err(|source| match wepay_error() {
+            Some(error) => WepayError::ApiError {
+                code: StatusCode::from_str(&error.code),
+                desc: error.desc,
+            },
+            None => WepayError::InvalidResponse {
+                status: response.status,
+                body: error::snippet(&response.body),
+                source,
+            },
+        })
+    }
+
     /// ดึงข้อมูลสินค้าจาก API
-    pub async fn product(&self) -> Result<response::Product, reqwest::Error> {
+    pub async fn product(&self) -> Result<response::Product, WepayError> {
         let url = format!("{}/comp_export.php?json", self.base_url);
 
-        // ส่ง GET request และแปลงผลลัพธ์เป็น response::Product
-        let response = self.client.get(&url).send().await?;
-        let result = response.json::<response::Product>().await?;
-        Ok(result)
+        // ส่ง GET request และแปลงผลลัพธ์เป็น response::Product (ส่งซ้ำได้อย่างปลอดภัย)
+        self.retry
+            .run(true, || async {
+                self.throttle().await;
+                let response = self.transport.send(HttpRequest::get(&url)).await?;
+                Self::read_json::<response::Product>(response)
+            })
+            .await
+    }
+
+    /// ฟังก์ชันภายในสำหรับดึงรายการสินค้าแบบมีเงื่อนไข (`If-None-Match`/`If-Modified-Since`) ให้ `Catalog`
+    ///
+    /// คืนค่า `None` เมื่อส่ง `validators` ไปและ server ตอบว่ารายการไม่เปลี่ยนแปลง (304 Not Modified)
+    pub(crate) async fn fetch_product(
+        &self,
+        validators: Option<&CacheValidators>,
+    ) -> Result<Option<(response::Product, CacheValidators)>, WepayError> {
+        let url = format!("{}/comp_export.php?json", self.base_url);
+
+        self.retry
+            .run(true, || async {
+                self.throttle().await;
+                let mut request = HttpRequest::get(&url);
+                if let Some(validators) = validators {
+                    if let Some(etag) = &validators.etag {
+                        request = request.header(IF_NONE_MATCH.as_str(), etag);
+                    }
+                    if let Some(last_modified) = &validators.last_modified {
+                        request = request.header(IF_MODIFIED_SINCE.as_str(), last_modified);
+                    }
+                }
+
+                let response = self.transport.send(request).await?;
+                if validators.is_some() && response.status == 304 {
+                    return Ok(None);
+                }
+
+                // หน้า error จะไม่ถูกเก็บลง cache
+                let validators = CacheValidators::from_response(&response);
+                let product = Self::read_json::<response::Product>(response)?;
+                Ok(Some((product, validators)))
+            })
+            .await
     }
 
     /// ดึงยอดเงินคงเหลือของบัญชีผ่าน API
     pub async fn balance(&self) -> Result<response::Balance, WepayError> {
         // ตรวจสอบว่า username/password ถูกกำหนดไว้หรือไม่
-        let username = self
-            .username
-            .as_ref()
-            .ok_or(WepayError::MissingCredentials("username"))?;
-        let password = self
-            .password
-            .as_ref()
-            .ok_or(WepayError::MissingCredentials("password"))?;
-
-        let url = format!("{}/client_api.json.php", self.base_url);
+        let (username, password) = self.credentials()?;
 
         // เตรียม parameters ที่จะส่งใน form request
         let params = [
-            ("username", username.as_str()),
-            ("password", password.as_str()),
+            ("username", username),
+            ("password", password),
             ("type", "balance_inquiry"),
         ];
 
-        // ส่ง POST request และเรียก handle_response เพื่อแปลง response
-        let response = self.client.post(&url).form(&params).send().await?;
-        Self::handle_response(response).await
+        // ส่ง POST request (ส่งซ้ำได้อย่างปลอดภัยเพราะเป็นการอ่านข้อมูลเท่านั้น)
+        let balance: response::Balance = self.post(&params, true).await?;
+
+        if let Some(guard) = &self.balance_guard {
+            guard.update(balance.available_balance);
+        }
+        Ok(balance)
     }
 
-    /// เติมเงินมือถือผ่าน API
-    pub async fn topup_mobile(
+    /// ตรวจสอบสถานะรายการจาก transaction_id หรือ dest_ref
+    ///
+    /// ใช้ในกรณีที่ไม่ได้รับ callback จาก wepay ถ้าไม่พบรายการจะได้ `StatusCode::TransactionNotFound`
+    pub async fn transaction_status(
         &self,
-        dest_ref: &str,
-        company: &str,
-        amount: &f32,
-        target: &str,
-        callback: &str,
-    ) -> Result<response::BillCommon, WepayError> {
-        if !validator::is_valid_ref(dest_ref) {
-            return Err(WepayError::InvalidFormat("dest_ref"));
-        }
+        query: &TransactionQuery,
+    ) -> Result<response::Transaction, WepayError> {
+        let (key, value) = match query {
+            TransactionQuery::TransactionId(id) => {
+                let rule = if id.is_empty() {
+                    Some(Rule::Empty)
+                } else if !id.chars().all(|c| c.is_ascii_digit()) {
+                    Some(Rule::NotDigits)
+                } else {
+                    None
+                };
+                if let Some(rule) = rule {
+                    return Err(ValidationError::new("transaction_id", rule)
+                        .with_value(id.as_str())
+                        .into());
+                }
+                ("transaction_id", id.as_str())
+            }
+            TransactionQuery::DestRef(dest_ref) => ("dest_ref", dest_ref.as_str()),
+        };
 
-        if !validator::is_thai_mobile_number(target) {
-            return Err(WepayError::InvalidFormat("target or pay_to_ref1"));
+        // ตรวจสอบว่า username/password ถูกกำหนดไว้หรือไม่
+        let (username, password) = self.credentials()?;
+
+        // เตรียม parameters ที่จะส่งใน form request
+        let params = [
+            ("username", username),
+            ("password", password),
+            ("type", "transaction_inquiry"),
+            (key, value),
+        ];
+
+        // ส่ง POST request (ส่งซ้ำได้อย่างปลอดภัยเพราะเป็นการอ่านข้อมูลเท่านั้น)
+        self.post(&params, true).await
+    }
+
+    /// ส่งคำสั่งซื้อใน `request` แบบ idempotent โดยใช้ dest_ref ของคำสั่งซื้อเป็นกุญแจ
+    ///
+    /// ถ้า dest_ref นี้เคยส่งสำเร็จแล้วจะคืนผลเดิมโดยไม่ส่งซ้ำ และถ้า wepay ตอบว่ารายการซ้ำ (30016)
+    /// หรือเกิดข้อผิดพลาดที่ไม่ทราบว่า wepay ได้รับคำขอหรือไม่ จะตรวจสอบสถานะรายการเดิมแทนการคืน error
+    ///
+    /// การเรียกพร้อมกันด้วย dest_ref เดียวกันผ่าน `Wepay` (และ clone) จะทำงานทีละรายการ
+    /// ส่วนระหว่าง process จะป้องกันด้วย `OrderStore::reserve`
+    pub async fn submit_once<R: OrderRequest>(
+        &self,
+        request: &R,
+    ) -> Result<Submission, WepayError> {
+        let dest_ref = request.dest_ref();
+        let _in_flight = self.in_flight.lock(dest_ref.as_str()).await;
+
+        // บันทึก Pending ถ้ายังไม่มีรายการ (created คือการเรียกครั้งนี้เป็นผู้สร้างรายการ)
+        let created = match self.order_store.reserve(dest_ref.as_str()).await? {
+            None => true,
+            Some(OrderState::Placed(bill)) => return Ok(Submission::Placed(bill)),
+            Some(OrderState::Resolved(transaction)) => {
+                return Ok(Submission::Resolved(transaction));
+            }
+            // ครั้งก่อนไม่ทราบผล ให้ตรวจสอบกับ wepay ก่อนส่งใหม่
+            Some(OrderState::Pending) => {
+                if let Some(submission) = self.resolve_order(dest_ref).await? {
+                    return Ok(submission);
+                }
+                false
+            }
+        };
+
+        match self.submit(request).await {
+            Ok(bill) => {
+                self.order_store
+                    .save(dest_ref.as_str(), &OrderState::Placed(bill.clone()))
+                    .await?;
+                Ok(Submission::Placed(bill))
+            }
+            // wepay เคยได้รับ dest_ref นี้แล้ว ให้คืนรายการเดิม
+            Err(e) if e.code() == Some(&StatusCode::DuplicateCallbackRefId) => {
+                match self.resolve_order(dest_ref).await? {
+                    Some(submission) => Ok(submission),
+                    None => Err(e),
+                }
+            }
+            // ไม่ทราบว่า wepay ได้รับคำขอหรือไม่ ถ้ายังไม่พบรายการให้คงสถานะ Pending ไว้
+            Err(e) if idempotency::is_ambiguous(&e) => match self.resolve_order(dest_ref).await {
+                Ok(Some(submission)) => Ok(submission),
+                _ => Err(e),
+            },
+            // คำสั่งซื้อถูกปฏิเสธแน่นอน ลบเฉพาะรายการที่สร้างในครั้งนี้
+            // (Pending จากครั้งก่อนยังต้องคงไว้เพราะคำขอครั้งนั้นอาจถึง wepay แล้ว)
+            Err(e) => {
+                if created {
+                    self.order_store.remove(dest_ref.as_str()).await?;
+                }
+                Err(e)
+            }
         }
+    }
 
-        if !validator::is_valid_url(callback) {
-            return Err(WepayError::InvalidFormat("callback or resp_url"));
+    /// ฟังก์ชันภายในสำหรับค้นหารายการเดิมจาก dest_ref และบันทึกผลลงที่เก็บคำสั่งซื้อ
+    async fn resolve_order(&self, dest_ref: &DestRef) -> Result<Option<Submission>, WepayError> {
+        let query = TransactionQuery::DestRef(dest_ref.clone());
+        match self.transaction_status(&query).await {
+            Ok(transaction) => {
+                self.order_store
+                    .save(
+                        dest_ref.as_str(),
+                        &OrderState::Resolved(transaction.clone()),
+                    )
+                    .await?;
+                Ok(Some(Submission::Resolved(transaction)))
+            }
+            Err(e) if e.code() == Some(&StatusCode::TransactionNotFound) => Ok(None),
+            Err(e) => Err(e),
         }
+    }
 
+    /// ส่งคำสั่งซื้อที่สร้างจาก builder ใน `request` (เช่น `MobileTopupRequest`) ผ่าน API
+    pub async fn submit<R: OrderRequest>(
+        &self,
+        request: &R,
+    ) -> Result<response::BillCommon, WepayError> {
         // ตรวจสอบว่า username/password ถูกกำหนดไว้หรือไม่
-        let username = self
-            .username
-            .as_ref()
-            .ok_or(WepayError::MissingCredentials("username"))?;
-        let password = self
-            .password
-            .as_ref()
-            .ok_or(WepayError::MissingCredentials("password"))?;
+        let (username, password) = self.credentials()?;
 
-        let url = format!("{}/client_api.json.php", self.base_url);
+        // ตรวจสอบจำนวนเงินกับรายการสินค้า (ถ้าเปิดใช้งาน) และคำนวณยอดที่จะถูกหัก
+        let cost = match &self.preflight {
+            Some(catalog) => catalog.quote(request).await?.total,
+            None => request.amount(),
+        };
+
+        // กันยอดเงินไว้จนกว่าจะได้ผลของคำสั่งซื้อ (ถ้าเปิดใช้งาน)
+        let reservation = match &self.balance_guard {
+            Some(guard) => {
+                if guard.available().is_none() {
+                    self.balance().await?;
+                }
+                Some(guard.reserve(cost)?)
+            }
+            None => None,
+        };
 
         // เตรียม parameters ที่จะส่งใน form request
-        let params = [
-            ("username", username.as_str()),
-            ("password", password.as_str()),
-            ("type", "mtopup"),
-            ("dest_ref", dest_ref),
-            ("pay_to_company", company),
-            ("pay_to_amount", &amount.to_string()),
-            ("pay_to_ref1", target),
-            ("resp_url", callback),
+        let order = request.params();
+        let mut params = vec![
+            ("username", username),
+            ("password", password),
+            ("type", request.order_type()),
         ];
+        params.extend(order.iter().map(|(key, value)| (*key, value.as_str())));
+
+        // ส่ง POST request (คำสั่งซื้อจะไม่ถูกส่งซ้ำถ้า wepay อาจได้รับคำขอแล้ว)
+        let result: Result<response::BillCommon, WepayError> = self.post(&params, false).await;
 
-        // ส่ง POST request และเรียก handle_response เพื่อแปลง response
-        let response = self.client.post(&url).form(&params).send().await?;
-        Self::handle_response(response).await
+        // ยอดเงินหลังทำรายการถูกหักแล้ว จึงคืนยอดที่กันไว้หลังบันทึกยอดใหม่
+        if let (Some(guard), Ok(bill)) = (&self.balance_guard, &result) {
+            guard.update(bill.balance);
+        }
+        drop(reservation);
+        result
+    }
+
+    /// เติมเงินมือถือผ่าน API
+    pub async fn topup_mobile(
+        &self,
+        dest_ref: &DestRef,
+        company: &str,
+        amount: Baht,
+        target: &MobileNumber,
+        callback: &CallbackUrl,
+    ) -> Result<response::BillCommon, WepayError> {
+        let request = MobileTopupRequest::builder()
+            .dest_ref(dest_ref)
+            .company(company)
+            .amount(amount)
+            .target(target)
+            .callback(callback)
+            .build()?;
+        self.submit(&request).await
+    }
+
+    /// ชำระบิล (ค่าไฟ ค่าน้ำ บัตรเครดิต ฯลฯ) ผ่าน API
+    ///
+    /// `company` คือรายการบริษัทจาก `product()` ซึ่งใช้ตรวจสอบจำนวนเงินและ Ref. ที่จำเป็นก่อนส่งคำขอ
+    pub async fn billpay(
+        &self,
+        dest_ref: &DestRef,
+        company: &response::Billpay,
+        amount: Baht,
+        refs: &BillpayRefs,
+        callback: &CallbackUrl,
+    ) -> Result<response::BillCommon, WepayError> {
+        let request = BillPaymentRequest::builder()
+            .dest_ref(dest_ref)
+            .company(company)
+            .amount(amount)
+            .refs(refs.clone())
+            .callback(callback)
+            .build()?;
+        self.submit(&request).await
+    }
+
+    /// ซื้อบัตรเงินสด (PIN) ผ่าน API
+    ///
+    /// `company` คือรายการบัตรเงินสดจาก `product()` โดย `amount` ต้องตรงกับราคาใน `denomination`
+    /// รหัส PIN/Serial ของบัตรจะถูกส่งกลับมาทาง callback (`resp_url`) ดู `callback::Notification::sms`
+    pub async fn cashcard(
+        &self,
+        dest_ref: &DestRef,
+        company: &response::Cashcard,
+        amount: Baht,
+        callback: &CallbackUrl,
+    ) -> Result<response::BillCommon, WepayError> {
+        let request = CashcardRequest::builder()
+            .dest_ref(dest_ref)
+            .company(company)
+            .amount(amount)
+            .callback(callback)
+            .build()?;
+        self.submit(&request).await
+    }
+
+    /// เติมเงินเกมผ่าน API
+    ///
+    /// `company` คือรายการเกมจาก `product()` ซึ่งใช้ตรวจสอบ Ref. ตาม `refs_format`,
+    /// เซิร์ฟเวอร์ตาม `gameservers` และราคาตาม `denomination` ก่อนส่งคำขอ
+    pub async fn gtopup(
+        &self,
+        dest_ref: &DestRef,
+        company: &response::Gtopup,
+        amount: Baht,
+        refs: &GtopupRefs,
+        server: Option<&str>,
+        callback: &CallbackUrl,
+    ) -> Result<response::BillCommon, WepayError> {
+        let mut builder = GameTopupRequest::builder()
+            .dest_ref(dest_ref)
+            .company(company)
+            .amount(amount)
+            .refs(refs.clone())
+            .callback(callback);
+        if let Some(server) = server {
+            builder = builder.server(server);
+        }
+        self.submit(&builder.build()?).await
     }
 }
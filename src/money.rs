@@ -0,0 +1,329 @@
+use crate::error::{Rule, ValidationError, WepayError};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::str::FromStr;
+
+/// จำนวนเงินบาทแบบ fixed-point ละเอียดถึงหน่วยสตางค์ (1 บาท = 100 สตางค์)
+///
+/// ใช้แทน `f32`/`f64` เพื่อหลีกเลี่ยงปัญหาการปัดเศษ เช่น 4.84 ที่กลายเป็น 4.8399999
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Baht(i64);
+
+impl Baht {
+    /// จำนวนเงินศูนย์บาท
+    pub const ZERO: Baht = Baht(0);
+
+    /// สร้างจำนวนเงินจากหน่วยสตางค์
+    pub const fn from_satang(satang: i64) -> Self {
+        Baht(satang)
+    }
+
+    /// สร้างจำนวนเงินจากหน่วยบาท (จำนวนเต็ม)
+    pub const fn from_baht(baht: i64) -> Self {
+        Baht(baht * 100)
+    }
+
+    /// จำนวนเงินในหน่วยสตางค์
+    pub const fn satang(&self) -> i64 {
+        self.0
+    }
+
+    /// ตรวจสอบว่าจำนวนเงินติดลบหรือไม่
+    pub const fn is_negative(&self) -> bool {
+        self.0 < 0
+    }
+
+    /// ตรวจสอบว่าจำนวนเงินมากกว่าศูนย์หรือไม่
+    pub const fn is_positive(&self) -> bool {
+        self.0 > 0
+    }
+
+    /// บวกจำนวนเงิน (`None` ถ้าเกินช่วงที่เก็บได้)
+    pub const fn checked_add(self, rhs: Baht) -> Option<Baht> {
+        match self.0.checked_add(rhs.0) {
+            Some(satang) => Some(Baht(satang)),
+            None => None,
+        }
+    }
+
+    /// ลบจำนวนเงิน (`None` ถ้าเกินช่วงที่เก็บได้)
+    pub const fn checked_sub(self, rhs: Baht) -> Option<Baht> {
+        match self.0.checked_sub(rhs.0) {
+            Some(satang) => Some(Baht(satang)),
+            None => None,
+        }
+    }
+
+    /// บวกจำนวนเงิน โดยให้ผลลัพธ์ไม่เกินค่าสูงสุดหรือต่ำสุดที่เก็บได้
+    pub const fn saturating_add(self, rhs: Baht) -> Baht {
+        Baht(self.0.saturating_add(rhs.0))
+    }
+
+    /// ลบจำนวนเงิน โดยให้ผลลัพธ์ไม่เกินค่าสูงสุดหรือต่ำสุดที่เก็บได้
+    pub const fn saturating_sub(self, rhs: Baht) -> Baht {
+        Baht(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl fmt::Display for Baht {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        write!(f, "{}{}.{:02}", sign, abs / 100, abs % 100)
+    }
+}
+
+impl FromStr for Baht {
+    type Err = WepayError;
+
+    /// แปลงสตริง เช่น "5", "4.8", "162.24" เป็นจำนวนเงิน (ปฏิเสธทศนิยมเกิน 2 ตำแหน่ง)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |rule| WepayError::from(ValidationError::new("amount", rule).with_value(s));
+
+        let s = s.trim();
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+
+        if whole.is_empty()
+            || !whole.chars().all(|c| c.is_ascii_digit())
+            || !fraction.chars().all(|c| c.is_ascii_digit())
+            || (digits.contains('.') && fraction.is_empty())
+        {
+            return Err(invalid(Rule::NotAmount));
+        }
+
+        // อนุญาตศูนย์ต่อท้ายเกิน 2 ตำแหน่ง (เช่น "1.500") แต่ไม่อนุญาตเศษสตางค์
+        let (cents, rest) = fraction.split_at(fraction.len().min(2));
+        if rest.chars().any(|c| c != '0') {
+            return Err(invalid(Rule::TooManyDecimals));
+        }
+
+        let whole: i64 = whole.parse().map_err(|_| invalid(Rule::NotAmount))?;
+        let cents: i64 = format!("{:0<2}", cents)
+            .parse()
+            .map_err(|_| invalid(Rule::NotAmount))?;
+        let satang = whole
+            .checked_mul(100)
+            .and_then(|v| v.checked_add(cents))
+            .ok_or_else(|| invalid(Rule::NotAmount))?;
+
+        Ok(Baht(if negative { -satang } else { satang }))
+    }
+}
+
+impl TryFrom<f64> for Baht {
+    type Error = WepayError;
+
+    /// แปลง `f64` เป็นจำนวนเงิน (ปฏิเสธค่าที่มีทศนิยมเกิน 2 ตำแหน่ง)
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        let invalid = |rule| {
+            WepayError::from(ValidationError::new("amount", rule).with_value(value.to_string()))
+        };
+
+        let satang = value * 100.0;
+        if !satang.is_finite() || satang.abs() > i64::MAX as f64 {
+            return Err(invalid(Rule::NotAmount));
+        }
+
+        // ยอมรับความคลาดเคลื่อนจากการแทนค่าทศนิยมของ f64 เท่านั้น
+        let rounded = satang.round();
+        if (satang - rounded).abs() > 1e-6 {
+            return Err(invalid(Rule::TooManyDecimals));
+        }
+
+        Ok(Baht(rounded as i64))
+    }
+}
+
+// operator ทำงานเหมือน `i64` (panic เมื่อเกินช่วงใน debug build) ใช้ `checked_*`/`saturating_*` กับยอดที่ไม่ได้จำกัดช่วง
+impl Add for Baht {
+    type Output = Baht;
+
+    fn add(self, rhs: Baht) -> Baht {
+        Baht(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Baht {
+    fn add_assign(&mut self, rhs: Baht) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Baht {
+    type Output = Baht;
+
+    fn sub(self, rhs: Baht) -> Baht {
+        Baht(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Baht {
+    fn sub_assign(&mut self, rhs: Baht) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Sum for Baht {
+    fn sum<I: Iterator<Item = Baht>>(iter: I) -> Baht {
+        iter.fold(Baht::ZERO, Add::add)
+    }
+}
+
+// ส่งออกเป็นสตริง เช่น "162.24" เพื่อไม่ให้สูญเสียความละเอียด
+impl Serialize for Baht {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+// wepay ส่งจำนวนเงินมาทั้งแบบสตริง ("162.24") และตัวเลข (4.84) จึงรองรับทั้งสองแบบ
+impl<'de> Deserialize<'de> for Baht {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BahtVisitor;
+
+        impl Visitor<'_> for BahtVisitor {
+            type Value = Baht;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an amount in baht with at most two decimals")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Baht, E> {
+                v.parse().map_err(E::custom)
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Baht, E> {
+                v.checked_mul(100)
+                    .map(Baht)
+                    .ok_or_else(|| E::custom("amount out of range"))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Baht, E> {
+                i64::try_from(v)
+                    .map_err(E::custom)
+                    .and_then(|v| self.visit_i64(v))
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Baht, E> {
+                Baht::try_from(v).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(BahtVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("5".parse::<Baht>().unwrap(), Baht::from_satang(500));
+        assert_eq!("4.8".parse::<Baht>().unwrap(), Baht::from_satang(480));
+        assert_eq!("4.84".parse::<Baht>().unwrap(), Baht::from_satang(484));
+        assert_eq!("162.240".parse::<Baht>().unwrap(), Baht::from_satang(16224));
+        assert_eq!("-1.05".parse::<Baht>().unwrap(), Baht::from_satang(-105));
+        assert_eq!("0.01".parse::<Baht>().unwrap(), Baht::from_satang(1));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!("4.845".parse::<Baht>().is_err()); // เศษสตางค์
+        assert!("".parse::<Baht>().is_err());
+        assert!("abc".parse::<Baht>().is_err());
+        assert!("1.".parse::<Baht>().is_err());
+        assert!(".5".parse::<Baht>().is_err());
+        assert!("1,000".parse::<Baht>().is_err());
+        assert!("1e3".parse::<Baht>().is_err());
+        assert!("99999999999999999999".parse::<Baht>().is_err()); // overflow
+    }
+
+    #[test]
+    fn test_parse_invalid_rule() {
+        let error = "4.845".parse::<Baht>().unwrap_err();
+        let error = error.validation().unwrap().get("amount").unwrap();
+        assert_eq!(error.rule, Rule::TooManyDecimals);
+        assert_eq!(error.value.as_deref(), Some("4.845"));
+
+        let error = "abc".parse::<Baht>().unwrap_err();
+        assert_eq!(
+            error.validation().unwrap().get("amount").unwrap().rule,
+            Rule::NotAmount
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Baht::from_satang(484).to_string(), "4.84");
+        assert_eq!(Baht::from_satang(500).to_string(), "5.00");
+        assert_eq!(Baht::from_satang(5).to_string(), "0.05");
+        assert_eq!(Baht::from_satang(-105).to_string(), "-1.05");
+        assert_eq!(Baht::ZERO.to_string(), "0.00");
+    }
+
+    #[test]
+    fn test_try_from_f64() {
+        assert_eq!(Baht::try_from(4.84).unwrap(), Baht::from_satang(484));
+        assert_eq!(
+            Baht::try_from(299987.74).unwrap(),
+            Baht::from_satang(29998774)
+        );
+        assert!(Baht::try_from(4.845).is_err());
+        assert!(Baht::try_from(f64::NAN).is_err());
+        assert!(Baht::try_from(f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Baht::from_satang(484);
+        let b = Baht::from_baht(5);
+        assert_eq!(a + b, Baht::from_satang(984));
+        assert_eq!(a - b, Baht::from_satang(-16));
+        assert!((a - b).is_negative());
+        assert_eq!([a, b].into_iter().sum::<Baht>(), Baht::from_satang(984));
+    }
+
+    #[test]
+    fn test_checked_and_saturating() {
+        let max = Baht::from_satang(i64::MAX);
+        let min = Baht::from_satang(i64::MIN);
+        let one = Baht::from_satang(1);
+
+        assert_eq!(max.checked_add(one), None);
+        assert_eq!(min.checked_sub(one), None);
+        assert_eq!(one.checked_sub(one), Some(Baht::ZERO));
+        assert_eq!(max.saturating_add(one), max);
+        assert_eq!(min.saturating_sub(one), min);
+
+        assert!(one.is_positive());
+        assert!(!Baht::ZERO.is_positive());
+    }
+
+    #[test]
+    fn test_serde() {
+        // รองรับทั้งสตริงและตัวเลข
+        let v: Vec<Baht> = serde_json::from_str(r#"["162.24", 4.84, 50000, 1.0]"#).unwrap();
+        assert_eq!(
+            v,
+            vec![
+                Baht::from_satang(16224),
+                Baht::from_satang(484),
+                Baht::from_baht(50000),
+                Baht::from_baht(1),
+            ]
+        );
+        assert!(serde_json::from_str::<Baht>("4.845").is_err());
+        assert_eq!(
+            serde_json::to_string(&Baht::from_satang(484)).unwrap(),
+            r#""4.84""#
+        );
+    }
+}
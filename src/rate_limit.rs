@@ -0,0 +1,127 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// ตัวจำกัดอัตราการส่งคำขอแบบ token bucket เพื่อหลีกเลี่ยง `InternalErrorTooManyRequests` (10003)
+///
+/// ใช้ร่วมกันระหว่าง `Wepay` ที่ clone มาจาก instance เดียวกัน คำขอที่เกินอัตราจะรอคิวแทนการถูกปฏิเสธ
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    bucket: Arc<Mutex<Bucket>>,
+    rate: f64,
+    burst: f64,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl RateLimiter {
+    /// สร้างตัวจำกัดที่อนุญาต `requests_per_second` คำขอต่อวินาที และส่งติดกันได้สูงสุด `burst` คำขอ
+    ///
+    /// # Panics
+    ///
+    /// ถ้า `requests_per_second` ไม่ใช่ค่าบวก
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        assert!(
+            requests_per_second > 0.0 && requests_per_second.is_finite(),
+            "requests_per_second must be positive"
+        );
+        let burst = f64::from(burst.max(1));
+
+        RateLimiter {
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: burst,
+                updated_at: Instant::now(),
+            })),
+            rate: requests_per_second,
+            burst,
+        }
+    }
+
+    /// รอจนกว่าจะได้รับอนุญาตให้ส่งคำขอ
+    pub async fn acquire(&self) {
+        let wait = self.reserve();
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// จอง token หนึ่งตัวและคืนระยะเวลาที่ต้องรอก่อนใช้ token นั้น
+    ///
+    /// token อาจติดลบได้ ทำให้คำขอที่มาทีหลังต้องรอคิวต่อจากคำขอก่อนหน้าตามลำดับ
+    fn reserve(&self) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+
+        // เติม token ตามเวลาที่ผ่านไป แต่ไม่เกินขนาด burst
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+        bucket.updated_at = now;
+
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_is_immediate() {
+        let limiter = RateLimiter::new(1.0, 3);
+
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+        assert!(limiter.reserve() > Duration::ZERO); // เกิน burst ต้องรอ
+    }
+
+    #[test]
+    fn test_waiters_queue_in_order() {
+        let limiter = RateLimiter::new(10.0, 1);
+
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+        let first = limiter.reserve();
+        let second = limiter.reserve();
+
+        // คำขอถัดไปต้องรอนานกว่าคำขอก่อนหน้าประมาณ 1/rate วินาที
+        assert!(first > Duration::from_millis(90) && first <= Duration::from_millis(100));
+        assert!(second > Duration::from_millis(190) && second <= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_shared_between_clones() {
+        let limiter = RateLimiter::new(1.0, 1);
+        let cloned = limiter.clone();
+
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+        assert!(cloned.reserve() > Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_waits() {
+        let limiter = RateLimiter::new(20.0, 1);
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+
+        // คำขอแรกผ่านทันที อีกสองคำขอรอคำขอละ 50ms
+        assert!(start.elapsed() >= Duration::from_millis(95));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rejects_zero_rate() {
+        RateLimiter::new(0.0, 1);
+    }
+}
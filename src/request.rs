@@ -0,0 +1,702 @@
+use crate::catalog::{Category, Company};
+use crate::error::{Rule, ValidationError, ValidationErrors, WepayError};
+use crate::money::Baht;
+use crate::response;
+use crate::types::{CallbackUrl, DestRef, IntoNewtype, MobileNumber};
+use crate::validator;
+use serde::Serialize;
+
+/// ข้อมูลอ้างอิงสำหรับการชำระบิล (Ref.1 - Ref.4 และบาร์โค้ด)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BillpayRefs {
+    pub ref1: Option<String>,
+    pub ref2: Option<String>,
+    pub ref3: Option<String>,
+    pub ref4: Option<String>,
+    pub barcode: Option<String>,
+}
+
+impl BillpayRefs {
+    /// สร้างข้อมูลอ้างอิงเปล่าสำหรับการชำระบิล
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// กำหนด Ref.1
+    pub fn ref1(mut self, value: impl Into<String>) -> Self {
+        self.ref1 = Some(value.into());
+        self
+    }
+
+    /// กำหนด Ref.2
+    pub fn ref2(mut self, value: impl Into<String>) -> Self {
+        self.ref2 = Some(value.into());
+        self
+    }
+
+    /// กำหนด Ref.3
+    pub fn ref3(mut self, value: impl Into<String>) -> Self {
+        self.ref3 = Some(value.into());
+        self
+    }
+
+    /// กำหนด Ref.4
+    pub fn ref4(mut self, value: impl Into<String>) -> Self {
+        self.ref4 = Some(value.into());
+        self
+    }
+
+    /// กำหนดบาร์โค้ด (ใช้กับบริษัทที่รับชำระด้วยบาร์โค้ดเท่านั้น)
+    pub fn barcode(mut self, value: impl Into<String>) -> Self {
+        self.barcode = Some(value.into());
+        self
+    }
+}
+
+/// ข้อมูลอ้างอิงผู้เล่นสำหรับการเติมเกม (Ref.1 - Ref.3)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GtopupRefs {
+    pub ref1: Option<String>,
+    pub ref2: Option<String>,
+    pub ref3: Option<String>,
+}
+
+impl GtopupRefs {
+    /// สร้างข้อมูลอ้างอิงเปล่าสำหรับการเติมเกม
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// กำหนด Ref.1 (โดยทั่วไปคือ ID ผู้เล่น)
+    pub fn ref1(mut self, value: impl Into<String>) -> Self {
+        self.ref1 = Some(value.into());
+        self
+    }
+
+    /// กำหนด Ref.2
+    pub fn ref2(mut self, value: impl Into<String>) -> Self {
+        self.ref2 = Some(value.into());
+        self
+    }
+
+    /// กำหนด Ref.3
+    pub fn ref3(mut self, value: impl Into<String>) -> Self {
+        self.ref3 = Some(value.into());
+        self
+    }
+}
+
+/// ตัวระบุรายการที่ต้องการตรวจสอบสถานะ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionQuery {
+    TransactionId(String), // transaction_id ที่ได้จาก `BillCommon`
+    DestRef(DestRef),      // dest_ref ที่กำหนดเองตอนส่งรายการ
+}
+
+/// Trait สำหรับคำสั่งซื้อทุกประเภทที่ส่งผ่าน `Wepay::submit`
+pub trait OrderRequest {
+    /// ประเภทสินค้าของคำสั่งซื้อ
+    fn category(&self) -> Category;
+
+    /// ประเภทคำสั่งซื้อ (`type`) ที่ส่งไปยัง API
+    fn order_type(&self) -> &'static str {
+        self.category().as_str()
+    }
+
+    /// dest_ref ของคำสั่งซื้อ
+    fn dest_ref(&self) -> &DestRef;
+
+    /// รหัสบริษัท (`pay_to_company`) ของคำสั่งซื้อ
+    fn company(&self) -> &str;
+
+    /// จำนวนเงิน (`pay_to_amount`) ของคำสั่งซื้อ
+    fn amount(&self) -> Baht;
+
+    /// parameters ของคำสั่งซื้อ (ไม่รวม username, password และ type)
+    fn params(&self) -> Vec<(&'static str, String)>;
+}
+
+/// ค่าที่ builder แปลงแล้ว (ข้อผิดพลาดจะถูกรายงานพร้อมข้อผิดพลาดอื่นตอน `build`)
+type Parsed<T> = Result<T, ValidationError>;
+
+/// ฟังก์ชันภายในสำหรับคืนจำนวนเงินที่ระบุและมากกว่าศูนย์ (บันทึกข้อผิดพลาดถ้าไม่ผ่าน)
+fn require_amount(errors: &mut ValidationErrors, amount: Option<Baht>) -> Option<Baht> {
+    let amount = errors.require("amount", amount)?;
+    if !amount.is_positive() {
+        errors
+            .push(ValidationError::new("amount", Rule::NotPositive).with_value(amount.to_string()));
+        return None;
+    }
+    Some(amount)
+}
+
+/// ฟังก์ชันภายในสำหรับตรวจสอบจำนวนเงินกับช่วงและราคาที่บริษัทกำหนด
+fn check_amount(errors: &mut ValidationErrors, company: Company<'_>, amount: Baht) {
+    if let Err(rule) = company.check_amount(amount) {
+        errors.push(
+            ValidationError::new("amount or pay_to_amount", rule).with_value(amount.to_string()),
+        );
+    }
+}
+
+/// คำสั่งเติมเงินมือถือ (`mtopup`)
+#[derive(Debug, Clone, Serialize)]
+pub struct MobileTopupRequest {
+    pub dest_ref: DestRef,
+    pub company: String,
+    pub amount: Baht,
+    pub target: MobileNumber,
+    pub callback: CallbackUrl,
+}
+
+/// Struct สำหรับสร้าง `MobileTopupRequest` พร้อมตรวจสอบข้อมูล
+#[derive(Debug, Default)]
+pub struct MobileTopupRequestBuilder {
+    dest_ref: Option<Parsed<DestRef>>,
+    company: Option<String>,
+    amount: Option<Baht>,
+    target: Option<Parsed<MobileNumber>>,
+    callback: Option<Parsed<CallbackUrl>>,
+}
+
+impl MobileTopupRequest {
+    /// เรียกใช้งาน builder เพื่อสร้างคำสั่งเติมเงินมือถือ
+    pub fn builder() -> MobileTopupRequestBuilder {
+        MobileTopupRequestBuilder::default()
+    }
+}
+
+impl MobileTopupRequestBuilder {
+    /// กำหนด dest_ref ของคำสั่งซื้อ (`DestRef` ใช้ได้ทันที ส่วนสตริงจะถูกตรวจสอบตอน `build`)
+    pub fn dest_ref(mut self, dest_ref: impl IntoNewtype<DestRef>) -> Self {
+        self.dest_ref = Some(dest_ref.into_newtype());
+        self
+    }
+
+    /// กำหนดรหัสบริษัทผู้ให้บริการ เช่น `TRMV`
+    pub fn company(mut self, company: impl Into<String>) -> Self {
+        self.company = Some(company.into());
+        self
+    }
+
+    /// กำหนดจำนวนเงินที่ต้องการเติม
+    pub fn amount(mut self, amount: Baht) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    /// กำหนดเบอร์มือถือที่ต้องการเติมเงิน (`MobileNumber` ใช้ได้ทันที ส่วนสตริงจะถูกตรวจสอบตอน `build`)
+    pub fn target(mut self, target: impl IntoNewtype<MobileNumber>) -> Self {
+        self.target = Some(target.into_newtype());
+        self
+    }
+
+    /// กำหนด URL ที่ wepay จะส่งผลการทำรายการกลับมา (`CallbackUrl` ใช้ได้ทันที ส่วนสตริงจะถูกตรวจสอบตอน `build`)
+    pub fn callback(mut self, callback: impl IntoNewtype<CallbackUrl>) -> Self {
+        self.callback = Some(callback.into_newtype());
+        self
+    }
+
+    /// ตรวจสอบข้อมูลและสร้างคำสั่งเติมเงินมือถือ (รวบรวมข้อมูลที่ไม่ผ่านทั้งหมดใน `WepayError::Validation`
+    /// รวมถึง dest_ref, เบอร์มือถือ และ URL ที่แปลงไม่สำเร็จ)
+    pub fn build(self) -> Result<MobileTopupRequest, WepayError> {
+        let mut errors = ValidationErrors::default();
+        let dest_ref = errors.require_valid("dest_ref", self.dest_ref);
+        let company = errors.require("company", self.company);
+        let amount = require_amount(&mut errors, self.amount);
+        let target = errors.require_valid("target", self.target);
+        let callback = errors.require_valid("callback", self.callback);
+
+        match (dest_ref, company, amount, target, callback) {
+            (Some(dest_ref), Some(company), Some(amount), Some(target), Some(callback))
+                if errors.is_empty() =>
+            {
+                Ok(MobileTopupRequest {
+                    dest_ref,
+                    company,
+                    amount,
+                    target,
+                    callback,
+                })
+            }
+            _ => Err(errors.into()),
+        }
+    }
+}
+
+impl OrderRequest for MobileTopupRequest {
+    fn category(&self) -> Category {
+        Category::Mtopup
+    }
+
+    fn dest_ref(&self) -> &DestRef {
+        &self.dest_ref
+    }
+
+    fn company(&self) -> &str {
+        &self.company
+    }
+
+    fn amount(&self) -> Baht {
+        self.amount
+    }
+
+    fn params(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("dest_ref", self.dest_ref.to_string()),
+            ("pay_to_company", self.company.clone()),
+            ("pay_to_amount", self.amount.to_string()),
+            ("pay_to_ref1", self.target.to_string()),
+            ("resp_url", self.callback.to_string()),
+        ]
+    }
+}
+
+/// คำสั่งชำระบิล (`billpay`)
+#[derive(Debug, Clone, Serialize)]
+pub struct BillPaymentRequest {
+    pub dest_ref: DestRef,
+    pub company: String,
+    pub amount: Baht,
+    pub refs: BillpayRefs,
+    pub callback: CallbackUrl,
+}
+
+/// Struct สำหรับสร้าง `BillPaymentRequest` พร้อมตรวจสอบข้อมูลกับรายการบริษัทจาก `product()`
+#[derive(Debug, Default)]
+pub struct BillPaymentRequestBuilder<'a> {
+    dest_ref: Option<Parsed<DestRef>>,
+    company: Option<&'a response::Billpay>,
+    amount: Option<Baht>,
+    refs: BillpayRefs,
+    callback: Option<Parsed<CallbackUrl>>,
+}
+
+impl BillPaymentRequest {
+    /// เรียกใช้งาน builder เพื่อสร้างคำสั่งชำระบิล
+    pub fn builder<'a>() -> BillPaymentRequestBuilder<'a> {
+        BillPaymentRequestBuilder::default()
+    }
+}
+
+impl<'a> BillPaymentRequestBuilder<'a> {
+    /// กำหนด dest_ref ของคำสั่งซื้อ (`DestRef` ใช้ได้ทันที ส่วนสตริงจะถูกตรวจสอบตอน `build`)
+    pub fn dest_ref(mut self, dest_ref: impl IntoNewtype<DestRef>) -> Self {
+        self.dest_ref = Some(dest_ref.into_newtype());
+        self
+    }
+
+    /// กำหนดบริษัทจากรายการ `billpay` ของ `product()`
+    pub fn company(mut self, company: &'a response::Billpay) -> Self {
+        self.company = Some(company);
+        self
+    }
+
+    /// กำหนดจำนวนเงินที่ต้องการชำระ
+    pub fn amount(mut self, amount: Baht) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    /// กำหนด Ref. และบาร์โค้ดของบิล
+    pub fn refs(mut self, refs: BillpayRefs) -> Self {
+        self.refs = refs;
+        self
+    }
+
+    /// กำหนด URL ที่ wepay จะส่งผลการทำรายการกลับมา (`CallbackUrl` ใช้ได้ทันที ส่วนสตริงจะถูกตรวจสอบตอน `build`)
+    pub fn callback(mut self, callback: impl IntoNewtype<CallbackUrl>) -> Self {
+        self.callback = Some(callback.into_newtype());
+        self
+    }
+
+    /// ตรวจสอบข้อมูลกับรายการบริษัทและสร้างคำสั่งชำระบิล (รวบรวมข้อมูลที่ไม่ผ่านทั้งหมด)
+    pub fn build(self) -> Result<BillPaymentRequest, WepayError> {
+        let mut errors = ValidationErrors::default();
+        let dest_ref = errors.require_valid("dest_ref", self.dest_ref);
+        let callback = errors.require_valid("callback", self.callback);
+        let company = errors.require("company", self.company);
+        let amount = require_amount(&mut errors, self.amount);
+        let refs = self.refs;
+
+        if let Some(company) = company {
+            // ตรวจสอบจำนวนเงินตามช่วงที่บริษัทกำหนด
+            if let Some(amount) = amount {
+                check_amount(&mut errors, Company::Billpay(company), amount);
+            }
+
+            // บริษัทที่รับเฉพาะบาร์โค้ดต้องระบุบาร์โค้ด นอกนั้นต้องระบุ Ref. ตามที่บริษัทกำหนด
+            if company.barcode_only {
+                check_present(&mut errors, "barcode", &refs.barcode);
+            } else {
+                for r in &company.refs {
+                    let (value, field) = match r.key {
+                        response::Key::Ref1 => (&refs.ref1, "ref1 or pay_to_ref1"),
+                        response::Key::Ref2 => (&refs.ref2, "ref2 or pay_to_ref2"),
+                        response::Key::Ref3 => (&refs.ref3, "ref3 or pay_to_ref3"),
+                    };
+                    check_present(&mut errors, field, value);
+                }
+            }
+        }
+
+        match (dest_ref, callback, company, amount) {
+            (Some(dest_ref), Some(callback), Some(company), Some(amount)) if errors.is_empty() => {
+                Ok(BillPaymentRequest {
+                    dest_ref,
+                    company: company.company_id.to_string(),
+                    amount,
+                    refs,
+                    callback,
+                })
+            }
+            _ => Err(errors.into()),
+        }
+    }
+}
+
+/// ฟังก์ชันภายในสำหรับตรวจสอบว่าระบุ Ref. ที่จำเป็นและไม่ว่างเปล่า
+fn check_present(errors: &mut ValidationErrors, field: &'static str, value: &Option<String>) {
+    match value.as_deref() {
+        None => errors.push(ValidationError::new(field, Rule::Missing)),
+        Some("") => errors.push(ValidationError::new(field, Rule::Empty)),
+        Some(_) => {}
+    }
+}
+
+impl OrderRequest for BillPaymentRequest {
+    fn category(&self) -> Category {
+        Category::Billpay
+    }
+
+    fn dest_ref(&self) -> &DestRef {
+        &self.dest_ref
+    }
+
+    fn company(&self) -> &str {
+        &self.company
+    }
+
+    fn amount(&self) -> Baht {
+        self.amount
+    }
+
+    fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![
+            ("dest_ref", self.dest_ref.to_string()),
+            ("pay_to_company", self.company.clone()),
+            ("pay_to_amount", self.amount.to_string()),
+            ("resp_url", self.callback.to_string()),
+        ];
+
+        // เพิ่ม Ref. และบาร์โค้ดเฉพาะที่มีการระบุ
+        let optional = [
+            ("pay_to_ref1", &self.refs.ref1),
+            ("pay_to_ref2", &self.refs.ref2),
+            ("pay_to_ref3", &self.refs.ref3),
+            ("pay_to_ref4", &self.refs.ref4),
+            ("barcode", &self.refs.barcode),
+        ];
+        for (key, value) in optional {
+            if let Some(value) = value {
+                params.push((key, value.clone()));
+            }
+        }
+
+        params
+    }
+}
+
+/// คำสั่งซื้อบัตรเงินสด (`cashcard`)
+#[derive(Debug, Clone, Serialize)]
+pub struct CashcardRequest {
+    pub dest_ref: DestRef,
+    pub company: String,
+    pub amount: Baht,
+    pub callback: CallbackUrl,
+}
+
+/// Struct สำหรับสร้าง `CashcardRequest` พร้อมตรวจสอบราคากับรายการบัตรจาก `product()`
+#[derive(Debug, Default)]
+pub struct CashcardRequestBuilder<'a> {
+    dest_ref: Option<Parsed<DestRef>>,
+    company: Option<&'a response::Cashcard>,
+    amount: Option<Baht>,
+    callback: Option<Parsed<CallbackUrl>>,
+}
+
+impl CashcardRequest {
+    /// เรียกใช้งาน builder เพื่อสร้างคำสั่งซื้อบัตรเงินสด
+    pub fn builder<'a>() -> CashcardRequestBuilder<'a> {
+        CashcardRequestBuilder::default()
+    }
+}
+
+impl<'a> CashcardRequestBuilder<'a> {
+    /// กำหนด dest_ref ของคำสั่งซื้อ (`DestRef` ใช้ได้ทันที ส่วนสตริงจะถูกตรวจสอบตอน `build`)
+    pub fn dest_ref(mut self, dest_ref: impl IntoNewtype<DestRef>) -> Self {
+        self.dest_ref = Some(dest_ref.into_newtype());
+        self
+    }
+
+    /// กำหนดบัตรจากรายการ `cashcard` ของ `product()`
+    pub fn company(mut self, company: &'a response::Cashcard) -> Self {
+        self.company = Some(company);
+        self
+    }
+
+    /// กำหนดราคาบัตร (ต้องตรงกับราคาใน `denomination`)
+    pub fn amount(mut self, amount: Baht) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    /// กำหนด URL ที่ wepay จะส่งผลการทำรายการ (รวมถึง PIN/Serial) กลับมา (`CallbackUrl` ใช้ได้ทันที ส่วนสตริงจะถูกตรวจสอบตอน `build`)
+    pub fn callback(mut self, callback: impl IntoNewtype<CallbackUrl>) -> Self {
+        self.callback = Some(callback.into_newtype());
+        self
+    }
+
+    /// ตรวจสอบข้อมูลกับรายการบัตรและสร้างคำสั่งซื้อบัตรเงินสด (รวบรวมข้อมูลที่ไม่ผ่านทั้งหมด)
+    pub fn build(self) -> Result<CashcardRequest, WepayError> {
+        let mut errors = ValidationErrors::default();
+        let dest_ref = errors.require_valid("dest_ref", self.dest_ref);
+        let callback = errors.require_valid("callback", self.callback);
+        let company = errors.require("company", self.company);
+        let amount = require_amount(&mut errors, self.amount);
+
+        if let (Some(company), Some(amount)) = (company, amount) {
+            check_amount(&mut errors, Company::Cashcard(company), amount);
+        }
+
+        match (dest_ref, callback, company, amount) {
+            (Some(dest_ref), Some(callback), Some(company), Some(amount)) if errors.is_empty() => {
+                Ok(CashcardRequest {
+                    dest_ref,
+                    company: company.company_id.clone(),
+                    amount,
+                    callback,
+                })
+            }
+            _ => Err(errors.into()),
+        }
+    }
+}
+
+impl OrderRequest for CashcardRequest {
+    fn category(&self) -> Category {
+        Category::Cashcard
+    }
+
+    fn dest_ref(&self) -> &DestRef {
+        &self.dest_ref
+    }
+
+    fn company(&self) -> &str {
+        &self.company
+    }
+
+    fn amount(&self) -> Baht {
+        self.amount
+    }
+
+    fn params(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("dest_ref", self.dest_ref.to_string()),
+            ("pay_to_company", self.company.clone()),
+            ("pay_to_amount", self.amount.to_string()),
+            ("resp_url", self.callback.to_string()),
+        ]
+    }
+}
+
+/// คำสั่งเติมเงินเกม (`gtopup`)
+#[derive(Debug, Clone, Serialize)]
+pub struct GameTopupRequest {
+    pub dest_ref: DestRef,
+    pub company: String,
+    pub amount: Baht,
+    pub refs: GtopupRefs,
+    pub server: Option<String>,
+    pub callback: CallbackUrl,
+}
+
+/// Struct สำหรับสร้าง `GameTopupRequest` พร้อมตรวจสอบข้อมูลกับรายการเกมจาก `product()`
+#[derive(Debug, Default)]
+pub struct GameTopupRequestBuilder<'a> {
+    dest_ref: Option<Parsed<DestRef>>,
+    company: Option<&'a response::Gtopup>,
+    amount: Option<Baht>,
+    refs: GtopupRefs,
+    server: Option<String>,
+    callback: Option<Parsed<CallbackUrl>>,
+}
+
+impl GameTopupRequest {
+    /// เรียกใช้งาน builder เพื่อสร้างคำสั่งเติมเงินเกม
+    pub fn builder<'a>() -> GameTopupRequestBuilder<'a> {
+        GameTopupRequestBuilder::default()
+    }
+}
+
+impl<'a> GameTopupRequestBuilder<'a> {
+    /// กำหนด dest_ref ของคำสั่งซื้อ (`DestRef` ใช้ได้ทันที ส่วนสตริงจะถูกตรวจสอบตอน `build`)
+    pub fn dest_ref(mut self, dest_ref: impl IntoNewtype<DestRef>) -> Self {
+        self.dest_ref = Some(dest_ref.into_newtype());
+        self
+    }
+
+    /// กำหนดเกมจากรายการ `gtopup` ของ `product()`
+    pub fn company(mut self, company: &'a response::Gtopup) -> Self {
+        self.company = Some(company);
+        self
+    }
+
+    /// กำหนดราคา (ต้องตรงกับราคาใน `denomination`)
+    pub fn amount(mut self, amount: Baht) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    /// กำหนด Ref. ของผู้เล่น
+    pub fn refs(mut self, refs: GtopupRefs) -> Self {
+        self.refs = refs;
+        self
+    }
+
+    /// กำหนดเซิร์ฟเวอร์ (ต้องตรงกับ `value` ใน `gameservers`)
+    pub fn server(mut self, server: impl Into<String>) -> Self {
+        self.server = Some(server.into());
+        self
+    }
+
+    /// กำหนด URL ที่ wepay จะส่งผลการทำรายการกลับมา (`CallbackUrl` ใช้ได้ทันที ส่วนสตริงจะถูกตรวจสอบตอน `build`)
+    pub fn callback(mut self, callback: impl IntoNewtype<CallbackUrl>) -> Self {
+        self.callback = Some(callback.into_newtype());
+        self
+    }
+
+    /// ตรวจสอบข้อมูลกับรายการเกมและสร้างคำสั่งเติมเงินเกม (รวบรวมข้อมูลที่ไม่ผ่านทั้งหมด)
+    pub fn build(self) -> Result<GameTopupRequest, WepayError> {
+        let mut errors = ValidationErrors::default();
+        let dest_ref = errors.require_valid("dest_ref", self.dest_ref);
+        let callback = errors.require_valid("callback", self.callback);
+        let company = errors.require("company", self.company);
+        let amount = require_amount(&mut errors, self.amount);
+        let refs = self.refs;
+
+        if let Some(company) = company {
+            if let Some(amount) = amount {
+                check_amount(&mut errors, Company::Gtopup(company), amount);
+            }
+
+            // ตรวจสอบ Ref. ตามรูปแบบที่เกมกำหนด
+            let formats = [
+                (&company.refs_format.ref1, &refs.ref1, "ref1 or pay_to_ref1"),
+                (&company.refs_format.ref2, &refs.ref2, "ref2 or pay_to_ref2"),
+                (&company.refs_format.ref3, &refs.ref3, "ref3 or pay_to_ref3"),
+            ];
+            for (pattern, value, field) in formats {
+                let Some(pattern) = pattern else { continue };
+                let Some(value) = value.as_deref() else {
+                    errors.push(ValidationError::new(field, Rule::Missing));
+                    continue;
+                };
+                match validator::matches_format(value, pattern) {
+                    Some(true) => {}
+                    Some(false) => errors.push(
+                        ValidationError::new(
+                            field,
+                            Rule::PatternMismatch {
+                                pattern: pattern.clone(),
+                            },
+                        )
+                        .with_value(value),
+                    ),
+                    None => errors.push(ValidationError::new(
+                        "refs_format",
+                        Rule::InvalidPattern {
+                            pattern: pattern.clone(),
+                        },
+                    )),
+                }
+            }
+
+            // ถ้าเกมมีหลายเซิร์ฟเวอร์ ต้องระบุเซิร์ฟเวอร์ที่อยู่ในรายการ
+            if !company.gameservers.is_empty()
+                && !company
+                    .gameservers
+                    .iter()
+                    .any(|s| Some(&s.value) == self.server.as_ref())
+            {
+                let allowed = company
+                    .gameservers
+                    .iter()
+                    .map(|s| s.value.clone())
+                    .collect();
+                let error =
+                    ValidationError::new("server or pay_to_server", Rule::NotAllowed { allowed });
+                errors.push(match &self.server {
+                    Some(server) => error.with_value(server.as_str()),
+                    None => error,
+                });
+            }
+        }
+
+        match (dest_ref, callback, company, amount) {
+            (Some(dest_ref), Some(callback), Some(company), Some(amount)) if errors.is_empty() => {
+                Ok(GameTopupRequest {
+                    dest_ref,
+                    company: company.company_id.clone(),
+                    amount,
+                    refs,
+                    server: self.server,
+                    callback,
+                })
+            }
+            _ => Err(errors.into()),
+        }
+    }
+}
+
+impl OrderRequest for GameTopupRequest {
+    fn category(&self) -> Category {
+        Category::Gtopup
+    }
+
+    fn dest_ref(&self) -> &DestRef {
+        &self.dest_ref
+    }
+
+    fn company(&self) -> &str {
+        &self.company
+    }
+
+    fn amount(&self) -> Baht {
+        self.amount
+    }
+
+    fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![
+            ("dest_ref", self.dest_ref.to_string()),
+            ("pay_to_company", self.company.clone()),
+            ("pay_to_amount", self.amount.to_string()),
+            ("resp_url", self.callback.to_string()),
+        ];
+
+        // เพิ่ม Ref. และเซิร์ฟเวอร์เฉพาะที่มีการระบุ
+        let optional = [
+            ("pay_to_ref1", &self.refs.ref1),
+            ("pay_to_ref2", &self.refs.ref2),
+            ("pay_to_ref3", &self.refs.ref3),
+            ("pay_to_server", &self.server),
+        ];
+        for (key, value) in optional {
+            if let Some(value) = value {
+                params.push((key, value.clone()));
+            }
+        }
+
+        params
+    }
+}
@@ -1,5 +1,8 @@
+use crate::money::Baht;
 use serde::{Deserialize, Serialize};
 use serde_with::{DisplayFromStr, serde_as};
+use std::fmt;
+use std::str::FromStr;
 
 /// Trait สำหรับ response types ทั้งหมด
 pub trait ApiResponse {
@@ -20,14 +23,11 @@ impl ApiResponse for ErrorResponse {
 }
 
 /// โครงสร้างสำหรับการตอบกลับของ API ตรวจสอบยอดคงเหลือ
-#[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Balance {
     pub code: String,
-    #[serde_as(as = "DisplayFromStr")]
-    pub ledger_balance: f64,
-    #[serde_as(as = "DisplayFromStr")]
-    pub available_balance: f64,
+    pub ledger_balance: Baht,
+    pub available_balance: Baht,
 }
 
 impl ApiResponse for Balance {
@@ -37,16 +37,14 @@ impl ApiResponse for Balance {
 }
 
 /// โครงสร้างสำหรับการตอบกลับของ API [billpay, mtopup, cashcard]
-#[serde_as]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BillCommon {
     pub code: String,
     pub bill_id: u32,
     pub transaction_id: String,
     pub queue_id: u32,
-    pub total_amount: f64,
-    #[serde_as(as = "DisplayFromStr")]
-    pub balance: f64,
+    pub total_amount: Baht,
+    pub balance: Baht,
 }
 
 impl ApiResponse for BillCommon {
@@ -55,6 +53,69 @@ impl ApiResponse for BillCommon {
     }
 }
 
+/// สถานะของรายการ (ใช้ทั้งใน callback และการตรวจสอบสถานะรายการ)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionStatus {
+    InProgress, // อยู่ระหว่างการทำรายการ
+    Success,    // ทำรายการสำเร็จ
+    Failed,     // ทำรายการไม่สำเร็จ (คืนเงินเข้าระบบแล้ว)
+}
+
+impl TransactionStatus {
+    /// แปลงสถานะเป็นค่าที่ wepay ใช้
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransactionStatus::InProgress => "1",
+            TransactionStatus::Success => "2",
+            TransactionStatus::Failed => "4",
+        }
+    }
+
+    /// แปลงค่าที่ wepay ส่งมาเป็นสถานะ (คืนค่า `None` ถ้าไม่รู้จัก)
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "1" => Some(TransactionStatus::InProgress),
+            "2" => Some(TransactionStatus::Success),
+            "4" => Some(TransactionStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TransactionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for TransactionStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_code(s).ok_or_else(|| format!("unknown transaction status: {}", s))
+    }
+}
+
+/// โครงสร้างสำหรับการตอบกลับของ API ตรวจสอบสถานะรายการ
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transaction {
+    pub code: String,
+    pub transaction_id: String,
+    pub dest_ref: String, // ตามที่ wepay ส่งมาโดยไม่ตรวจสอบรูปแบบซ้ำ (ข้อมูลจาก server ถือเป็นข้อมูลจริง)
+    #[serde_as(as = "DisplayFromStr")]
+    pub status: TransactionStatus,
+    pub operator_trxnsid: Option<String>,
+    pub sms: Option<String>,
+}
+
+impl ApiResponse for Transaction {
+    fn code(&self) -> &str {
+        &self.code
+    }
+}
+
 /// Generic enum สำหรับ API response
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -81,9 +142,9 @@ pub struct Data {
 pub struct Billpay {
     pub company_id: CompanyId,
     pub company_name: String,
-    pub fee: i64,
-    pub minimum_amount: f64,
-    pub maximum_amount: i64,
+    pub fee: Baht,
+    pub minimum_amount: Baht,
+    pub maximum_amount: Baht,
     pub barcode_only: bool,
     pub refs: Vec<Ref>,
     pub additional_info: String,
@@ -96,6 +157,74 @@ pub enum CompanyId {
     String(String),
 }
 
+impl fmt::Display for CompanyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompanyId::Integer(id) => write!(f, "{}", id),
+            CompanyId::String(id) => write!(f, "{}", id),
+        }
+    }
+}
+
+impl CompanyId {
+    /// รหัสบริษัทในรูปแบบที่ใช้เปรียบเทียบได้
+    pub fn key(&self) -> CompanyKey {
+        CompanyKey::from(self)
+    }
+}
+
+/// รหัสบริษัทที่ normalize แล้วสำหรับค้นหาและเปรียบเทียบ
+///
+/// `CompanyId::Integer(5)`, `CompanyId::String("5")` และ `" 5 "` ถือเป็นรหัสเดียวกัน
+/// ตัวอักษรภาษาอังกฤษจะถูกแปลงเป็นตัวพิมพ์ใหญ่ (`trmv` เป็น `TRMV`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CompanyKey(String);
+
+impl CompanyKey {
+    /// สร้างรหัสบริษัทจากสตริง
+    pub fn new(id: &str) -> Self {
+        CompanyKey(id.trim().to_ascii_uppercase())
+    }
+
+    /// รหัสบริษัทในรูปแบบสตริง
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for CompanyKey {
+    fn from(id: &str) -> Self {
+        CompanyKey::new(id)
+    }
+}
+
+impl From<&String> for CompanyKey {
+    fn from(id: &String) -> Self {
+        CompanyKey::new(id)
+    }
+}
+
+impl From<&CompanyKey> for CompanyKey {
+    fn from(key: &CompanyKey) -> Self {
+        key.clone()
+    }
+}
+
+impl From<&CompanyId> for CompanyKey {
+    fn from(id: &CompanyId) -> Self {
+        match id {
+            CompanyId::Integer(id) => CompanyKey(id.to_string()),
+            CompanyId::String(id) => CompanyKey::new(id),
+        }
+    }
+}
+
+impl fmt::Display for CompanyKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Ref {
     pub key: Key,
@@ -114,13 +243,13 @@ pub enum Key {
 pub struct Cashcard {
     pub company_id: String,
     pub company_name: String,
-    pub fee: i64,
+    pub fee: Baht,
     pub denomination: Vec<Denomination>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Denomination {
-    pub price: f64,
+    pub price: Baht,
     pub description: Option<String>,
 }
 
@@ -128,7 +257,7 @@ pub struct Denomination {
 pub struct Gtopup {
     pub company_id: String,
     pub company_name: String,
-    pub fee: i64,
+    pub fee: Baht,
     pub denomination: Vec<Denomination>,
     pub congestion: Vec<Option<serde_json::Value>>,
     pub gameservers: Vec<Gameserver>,
@@ -152,9 +281,9 @@ pub struct RefsFormat {
 pub struct Mtopup {
     pub company_id: String,
     pub company_name: String,
-    pub fee: i64,
-    pub minimum_amount: f64,
-    pub maximum_amount: i64,
+    pub fee: Baht,
+    pub minimum_amount: Baht,
+    pub maximum_amount: Baht,
     pub refundable: bool,
     pub denomination: Vec<Denomination>,
 }
@@ -0,0 +1,212 @@
+use crate::error::{StatusCode, WepayError};
+use rand::Rng;
+use std::future::Future;
+use std::time::Duration;
+
+/// นโยบายการลองส่งคำขอซ้ำเมื่อเกิดข้อผิดพลาดชั่วคราว (exponential backoff พร้อม jitter)
+///
+/// คำขอที่ทำซ้ำได้อย่างปลอดภัย (`balance`, `product`, `transaction_status`) จะถูกส่งซ้ำเมื่อได้รับ
+/// รหัสสถานะที่กำหนด หรือเกิดข้อผิดพลาดระดับ HTTP ส่วนคำสั่งซื้อ (topup, billpay ฯลฯ)
+/// จะส่งซ้ำเฉพาะเมื่อเชื่อมต่อไปยัง wepay ไม่ได้เลยเท่านั้น เพื่อป้องกันการทำรายการซ้ำ
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+    retryable_codes: Vec<StatusCode>,
+}
+
+// ค่าเริ่มต้นคือส่งคำขอซ้ำสูงสุด 3 ครั้ง
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RetryPolicy {
+    /// สร้างนโยบายพร้อมค่าเริ่มต้น (3 ครั้ง, เริ่มที่ 200ms, สูงสุด 5 วินาที, มี jitter)
+    pub fn new() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            jitter: true,
+            retryable_codes: vec![
+                StatusCode::InternalErrorDb,
+                StatusCode::InternalErrorTooManyRequests,
+            ],
+        }
+    }
+
+    /// นโยบายที่ไม่ส่งคำขอซ้ำเลย (ค่าเริ่มต้นของ `WepayBuilder`)
+    pub fn none() -> Self {
+        Self::new().max_attempts(1)
+    }
+
+    /// กำหนดจำนวนครั้งสูงสุดที่ส่งคำขอ (รวมครั้งแรก)
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// กำหนดระยะเวลารอก่อนส่งซ้ำครั้งแรก (ครั้งต่อไปจะเพิ่มเป็นสองเท่า)
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// กำหนดระยะเวลารอสูงสุดระหว่างการส่งซ้ำ
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// เปิด/ปิดการสุ่มระยะเวลารอ (jitter) เพื่อไม่ให้หลาย client ส่งซ้ำพร้อมกัน
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// กำหนดรหัสสถานะจาก API ที่ถือว่าเป็นข้อผิดพลาดชั่วคราว
+    pub fn retryable_codes(mut self, codes: impl IntoIterator<Item = StatusCode>) -> Self {
+        self.retryable_codes = codes.into_iter().collect();
+        self
+    }
+
+    /// ระยะเวลารอก่อนส่งคำขอซ้ำหลังจากครั้งที่ `attempt` ล้มเหลว (เริ่มที่ 1)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff);
+
+        if self.jitter {
+            // equal jitter: รออย่างน้อยครึ่งหนึ่งของ backoff แล้วสุ่มส่วนที่เหลือ
+            let half = backoff / 2;
+            half + half.mul_f64(rand::rng().random::<f64>())
+        } else {
+            backoff
+        }
+    }
+
+    /// ตรวจสอบว่าข้อผิดพลาดนี้ควรส่งคำขอซ้ำหรือไม่
+    ///
+    /// `idempotent` ระบุว่าคำขอนี้ส่งซ้ำได้อย่างปลอดภัยแม้ wepay จะได้รับคำขอแล้ว
+    pub fn should_retry(&self, error: &WepayError, idempotent: bool) -> bool {
+        match error {
+            WepayError::RequestError(e) => {
+                // เชื่อมต่อไม่ได้เลย แปลว่าคำขอยังไม่ถึง wepay จึงส่งซ้ำได้เสมอ
+                e.is_connect()
+                    || (idempotent
+                        && (e.is_timeout()
+                            || e.is_request()
+                            || e.status().is_some_and(|s| s.is_server_error())))
+            }
+            WepayError::ApiError { code, .. } => idempotent && self.retryable_codes.contains(code),
+            WepayError::HttpError { status, code, .. } => {
+                idempotent
+                    && match code {
+                        Some(code) => self.retryable_codes.contains(code),
+                        None => *status >= 500,
+                    }
+            }
+            _ => false,
+        }
+    }
+
+    /// ฟังก์ชันภายในสำหรับเรียก `f` ซ้ำตามนโยบายจนกว่าจะสำเร็จหรือครบจำนวนครั้ง
+    pub(crate) async fn run<T, F, Fut>(&self, idempotent: bool, mut f: F) -> Result<T, WepayError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, WepayError>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match f().await {
+                Err(e) if attempt < self.max_attempts && self.should_retry(&e, idempotent) => {
+                    tokio::time::sleep(self.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{Rule, ValidationError};
+
+    fn api_error(code: StatusCode) -> WepayError {
+        WepayError::ApiError {
+            code,
+            desc: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_backoff_exponential() {
+        let policy = RetryPolicy::new()
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(1000))
+            .jitter(false);
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(5), Duration::from_millis(1000)); // ไม่เกิน max_backoff
+        assert_eq!(policy.backoff(100), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_backoff_jitter() {
+        let policy = RetryPolicy::new()
+            .initial_backoff(Duration::from_millis(100))
+            .jitter(true);
+
+        for _ in 0..100 {
+            let backoff = policy.backoff(2);
+            assert!(backoff >= Duration::from_millis(100));
+            assert!(backoff <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn test_should_retry_api_codes() {
+        let policy = RetryPolicy::new();
+
+        assert!(policy.should_retry(&api_error(StatusCode::InternalErrorDb), true));
+        assert!(policy.should_retry(&api_error(StatusCode::InternalErrorTooManyRequests), true));
+        assert!(!policy.should_retry(&api_error(StatusCode::InsufficientFunds), true));
+
+        // คำสั่งซื้อไม่ส่งซ้ำเมื่อ wepay ได้รับคำขอแล้ว
+        assert!(!policy.should_retry(&api_error(StatusCode::InternalErrorDb), false));
+
+        // HTTP error ที่ไม่มีรหัสจาก wepay ส่งซ้ำเฉพาะ 5xx
+        let http_error = |status, code| WepayError::HttpError {
+            status,
+            code,
+            body: String::new(),
+        };
+        assert!(policy.should_retry(&http_error(502, None), true));
+        assert!(!policy.should_retry(&http_error(404, None), true));
+        assert!(!policy.should_retry(&http_error(502, None), false));
+        assert!(policy.should_retry(
+            &http_error(429, Some(StatusCode::InternalErrorTooManyRequests)),
+            true
+        ));
+
+        // ไม่ส่งซ้ำเมื่อข้อมูลไม่ถูกต้อง
+        let invalid = ValidationError::new("dest_ref", Rule::Empty).into();
+        assert!(!policy.should_retry(&invalid, true));
+    }
+
+    #[test]
+    fn test_max_attempts_minimum() {
+        let policy = RetryPolicy::new().max_attempts(0);
+        assert_eq!(policy.max_attempts, 1);
+    }
+}
@@ -0,0 +1,586 @@
+use crate::WepayBuilder;
+use crate::catalog::{Category, ProductIndex};
+use crate::error::StatusCode;
+use crate::money::Baht;
+use crate::response::{
+    Balance, BillCommon, ErrorResponse, Product, Transaction, TransactionStatus,
+};
+use crate::types::{CallbackUrl, DestRef};
+use axum::{
+    Router,
+    body::Bytes,
+    extract::State,
+    http::header::CONTENT_TYPE,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use url::form_urlencoded;
+
+mod scenario;
+
+pub use scenario::{Fault, Scenario, Target};
+
+/// รายการสินค้าเริ่มต้นของ simulator (มีบริษัทอย่างน้อยหนึ่งรายการในทุกประเภท)
+const DEFAULT_PRODUCT: &str = r#"{"data": {
+    "mtopup": [
+        {"company_id": "TRMV", "company_name": "True Move", "fee": 0, "minimum_amount": 5, "maximum_amount": 1000, "refundable": false, "denomination": []},
+        {"company_id": "AIS", "company_name": "AIS", "fee": 0, "minimum_amount": 10, "maximum_amount": 1000, "refundable": false, "denomination": []}
+    ],
+    "cashcard": [
+        {"company_id": "GARENA", "company_name": "Garena Shells", "fee": 0, "denomination": [{"price": 50, "description": "50 Shells"}, {"price": 100, "description": "100 Shells"}]}
+    ],
+    "gtopup": [
+        {"company_id": "ROV", "company_name": "RoV", "fee": 0, "denomination": [{"price": 10, "description": null}, {"price": 50, "description": null}], "congestion": [], "gameservers": [], "refs_format": {"ref1": "^[0-9]{6,20}$", "ref2": null, "ref3": null}}
+    ],
+    "billpay": [
+        {"company_id": 12, "company_name": "การไฟฟ้าส่วนภูมิภาค", "fee": 5, "minimum_amount": 1, "maximum_amount": 50000, "barcode_only": false, "refs": [{"key": "ref1", "name": "Customer No."}], "additional_info": ""}
+    ]
+}}"#;
+
+/// Struct สำหรับสร้าง `Simulator` ด้วยตัวเลือกที่กำหนดเอง
+pub struct SimulatorBuilder {
+    username: String,
+    password: String,
+    balance: Baht,
+    product: Option<Product>,
+    callback_delay: Duration,
+    outcome: TransactionStatus,
+    scenario: Scenario,
+}
+
+/// เซิร์ฟเวอร์ wepay จำลองที่ทำงานภายใน process สำหรับทดสอบ (feature `test-support`)
+///
+/// รองรับ `client_api.json.php` (balance, mtopup, billpay, cashcard, gtopup และ transaction_inquiry)
+/// และ `comp_export.php` โดยหักยอดเงินจำลองตามรายการสินค้า ปฏิเสธ dest_ref ที่ซ้ำ
+/// และ POST callback ไปยัง `resp_url` หลังรับคำสั่งซื้อ เซิร์ฟเวอร์จะหยุดเมื่อ `Simulator` ถูก drop
+///
+/// ใช้ `Scenario` เพื่อจำลองความผิดปกติ เช่น รหัสสถานะ error, response ที่ช้า, connection ที่ถูกตัด
+/// หรือ callback ที่ซ้ำและผิดลำดับ
+///
+/// ```no_run
+/// # use wepay_rs::{money::Baht, simulator::Simulator};
+/// # async fn run() {
+/// let simulator = Simulator::builder()
+///     .balance(Baht::from_baht(1000))
+///     .start()
+///     .await
+///     .unwrap();
+/// let wepay = simulator.wepay().build().unwrap();
+/// # }
+/// ```
+pub struct Simulator {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+    task: JoinHandle<()>,
+}
+
+/// คำสั่งซื้อที่ simulator ได้รับ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulatedOrder {
+    pub transaction_id: String,
+    pub dest_ref: String,
+    pub category: Category,
+    pub company: String,
+    pub amount: Baht,              // จำนวนเงินของคำสั่งซื้อ
+    pub total: Baht,               // ยอดที่หักจากบัญชี (amount + fee)
+    pub resp_url: String,          // URL ที่ส่ง callback
+    pub status: TransactionStatus, // สถานะล่าสุดของรายการ
+    pub acknowledged: bool,        // ปลายทางตอบรับ callback ด้วย `SUCCEED|UPDATE_ID=...` แล้ว
+}
+
+struct Shared {
+    username: String,
+    password: String,
+    product: ProductIndex,
+    callback_delay: Duration,
+    outcome: TransactionStatus,
+    client: reqwest::Client,
+    ledger: Mutex<Ledger>,
+    scenario: Mutex<Scenario>,
+}
+
+struct Ledger {
+    available: Baht,
+    orders: Vec<SimulatedOrder>,
+    next_id: u32,
+}
+
+// กำหนดค่าเริ่มต้นของ SimulatorBuilder
+impl Default for SimulatorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimulatorBuilder {
+    /// สร้าง instance ใหม่ของ SimulatorBuilder (ยอดเงินเริ่มต้น 0 บาท และรายการทำสำเร็จทันที)
+    pub fn new() -> Self {
+        SimulatorBuilder {
+            username: "simulator".to_string(),
+            password: "simulator".to_string(),
+            balance: Baht::ZERO,
+            product: None,
+            callback_delay: Duration::ZERO,
+            outcome: TransactionStatus::Success,
+            scenario: Scenario::new(),
+        }
+    }
+
+    /// กำหนด username/password ที่ simulator ยอมรับ
+    pub fn credentials(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.username = username.into();
+        self.password = password.into();
+        self
+    }
+
+    /// กำหนดยอดเงินเริ่มต้นของบัญชี
+    pub fn balance(mut self, balance: Baht) -> Self {
+        self.balance = balance;
+        self
+    }
+
+    /// กำหนดรายการสินค้าที่ใช้ตรวจสอบคำสั่งซื้อและตอบ `comp_export.php`
+    pub fn product(mut self, product: Product) -> Self {
+        self.product = Some(product);
+        self
+    }
+
+    /// กำหนดระยะเวลาหลังรับคำสั่งซื้อก่อนทำรายการเสร็จและส่ง callback
+    pub fn callback_delay(mut self, delay: Duration) -> Self {
+        self.callback_delay = delay;
+        self
+    }
+
+    /// กำหนดผลของรายการ (`Failed` จะคืนเงินเข้าบัญชี)
+    pub fn outcome(mut self, outcome: TransactionStatus) -> Self {
+        self.outcome = outcome;
+        self
+    }
+
+    /// กำหนดลำดับความผิดปกติที่ใช้กับคำขอที่ได้รับ
+    pub fn scenario(mut self, scenario: Scenario) -> Self {
+        self.scenario = scenario;
+        self
+    }
+
+    /// เริ่มเซิร์ฟเวอร์ที่ `127.0.0.1` บน port ที่ว่าง
+    pub async fn start(self) -> std::io::Result<Simulator> {
+        let product = self.product.unwrap_or_else(|| {
+            serde_json::from_str(DEFAULT_PRODUCT).expect("default product is valid")
+        });
+
+        let shared = Arc::new(Shared {
+            username: self.username,
+            password: self.password,
+            product: ProductIndex::new(product),
+            callback_delay: self.callback_delay,
+            outcome: self.outcome,
+            client: reqwest::Client::new(),
+            ledger: Mutex::new(Ledger {
+                available: self.balance,
+                orders: Vec::new(),
+                next_id: 394209456,
+            }),
+            scenario: Mutex::new(self.scenario),
+        });
+
+        let router = Router::new()
+            .route("/client_api.json.php", post(client_api))
+            .route("/comp_export.php", get(comp_export))
+            .with_state(shared.clone());
+
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let addr = listener.local_addr()?;
+        let task = tokio::spawn(async move {
+            let _ = axum::serve(listener, router).await;
+        });
+
+        Ok(Simulator { addr, shared, task })
+    }
+}
+
+impl Simulator {
+    /// สร้าง SimulatorBuilder ใหม่
+    pub fn builder() -> SimulatorBuilder {
+        SimulatorBuilder::new()
+    }
+
+    /// URL ของ simulator สำหรับ `WepayBuilder::base_url`
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// WepayBuilder ที่กำหนด base_url และ username/password ของ simulator ไว้แล้ว
+    pub fn wepay(&self) -> WepayBuilder {
+        WepayBuilder::new()
+            .base_url(self.base_url())
+            .username(&self.shared.username)
+            .password(&self.shared.password)
+    }
+
+    /// ยอดเงินที่ใช้ได้ของบัญชีจำลอง
+    pub fn balance(&self) -> Baht {
+        self.shared.ledger().available
+    }
+
+    /// กำหนดยอดเงินที่ใช้ได้ของบัญชีจำลอง (เช่น จำลองการเติมเงินเข้าบัญชี)
+    pub fn set_balance(&self, balance: Baht) {
+        self.shared.ledger().available = balance;
+    }
+
+    /// คำสั่งซื้อทั้งหมดตามลำดับที่ได้รับ
+    pub fn orders(&self) -> Vec<SimulatedOrder> {
+        self.shared.ledger().orders.clone()
+    }
+
+    /// ค้นหาคำสั่งซื้อจาก dest_ref
+    pub fn order(&self, dest_ref: &str) -> Option<SimulatedOrder> {
+        self.shared
+            .ledger()
+            .orders
+            .iter()
+            .find(|o| o.dest_ref == dest_ref)
+            .cloned()
+    }
+
+    /// แทนที่ลำดับความผิดปกติเดิมด้วย `scenario`
+    pub fn set_scenario(&self, scenario: Scenario) {
+        *self.shared.scenario() = scenario;
+    }
+
+    /// ลำดับความผิดปกติที่เหลืออยู่ (ใช้ `Scenario::is_finished` ตรวจสอบว่าใช้ครบแล้วหรือไม่)
+    pub fn scenario(&self) -> Scenario {
+        self.shared.scenario().clone()
+    }
+}
+
+impl Drop for Simulator {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn client_api(State(shared): State<Arc<Shared>>, body: Bytes) -> Response {
+    let fields: HashMap<String, String> = form_urlencoded::parse(&body).into_owned().collect();
+    let fault = shared.next_fault(Target::of(field(&fields, "type")));
+
+    with_fault(fault.clone(), move || {
+        match shared.handle(&fields, fault.as_ref()) {
+            Ok(body) => json(body),
+            Err(code) => json(ErrorResponse {
+                desc: describe(&code).to_string(),
+                code: code.as_str().to_string(),
+            }),
+        }
+    })
+    .await
+}
+
+async fn comp_export(State(shared): State<Arc<Shared>>) -> Response {
+    let fault = shared.next_fault(Target::Product);
+    with_fault(fault, move || json(shared.product.product().as_ref())).await
+}
+
+/// ฟังก์ชันภายในสำหรับใช้ความผิดปกติก่อนและหลังประมวลผลคำขอด้วย `process`
+async fn with_fault(
+    fault: Option<Fault>,
+    process: impl FnOnce() -> Response + Send + 'static,
+) -> Response {
+    if let Some(response) = fault.as_ref().and_then(Fault::response) {
+        return response;
+    }
+
+    // ประมวลผลใน task แยก เพื่อให้คำสั่งซื้อถูกรับแม้ client จะตัด connection ระหว่างรอ (เช่น หมดเวลารอ)
+    let delay = match fault {
+        Some(Fault::Delay(delay)) => delay,
+        _ => Duration::ZERO,
+    };
+    let response = tokio::spawn(async move {
+        tokio::time::sleep(delay).await;
+        process()
+    })
+    .await
+    .expect("simulator handler panicked");
+
+    match fault {
+        Some(Fault::DropConnection) => Fault::dropped(),
+        _ => response,
+    }
+}
+
+/// ฟังก์ชันภายในสำหรับตอบกลับเป็น JSON (HTTP 200 เช่นเดียวกับ wepay แม้จะเป็น error)
+fn json(body: impl Serialize) -> Response {
+    let body = serde_json::to_string(&body).expect("response is serializable");
+    ([(CONTENT_TYPE, "application/json")], body).into_response()
+}
+
+/// คำอธิบายของรหัสสถานะที่ simulator ส่งกลับ
+fn describe(code: &StatusCode) -> &'static str {
+    match code {
+        StatusCode::InternalErrorIncomplete => "Incomplete request",
+        StatusCode::InvalidCredentialsApi => "Username is not allowed to use API",
+        StatusCode::TransactionNotFound => "Transaction not found",
+        StatusCode::InvalidCallbackUrl => "Invalid callback URL",
+        StatusCode::InvalidCallbackRefId => "Invalid callback reference ID",
+        StatusCode::InvalidPaymentAmount => "Invalid payment amount",
+        StatusCode::InvalidCompany => "Invalid company",
+        StatusCode::InvalidRef1 => "Invalid Ref.1",
+        StatusCode::DuplicateCallbackRefId => "Duplicate callback reference ID",
+        StatusCode::InsufficientFunds => "Insufficient funds",
+        _ => "Error",
+    }
+}
+
+impl Shared {
+    fn ledger(&self) -> MutexGuard<'_, Ledger> {
+        self.ledger.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn scenario(&self) -> MutexGuard<'_, Scenario> {
+        self.scenario.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn next_fault(&self, target: Target) -> Option<Fault> {
+        self.scenario().next(target)
+    }
+
+    /// ฟังก์ชันภายในสำหรับประมวลผลคำขอไปยัง client_api ตาม `type`
+    fn handle(
+        self: &Arc<Self>,
+        fields: &HashMap<String, String>,
+        fault: Option<&Fault>,
+    ) -> Result<serde_json::Value, StatusCode> {
+        let field = |key| field(fields, key);
+
+        if field("username") != Some(self.username.as_str())
+            || field("password") != Some(self.password.as_str())
+        {
+            return Err(StatusCode::InvalidCredentialsApi);
+        }
+
+        let category = match field("type") {
+            Some("balance_inquiry") => return Ok(to_value(self.balance())),
+            Some("transaction_inquiry") => {
+                let transaction = self.transaction(field("transaction_id"), field("dest_ref"))?;
+                return Ok(to_value(transaction));
+            }
+            Some("mtopup") => Category::Mtopup,
+            Some("cashcard") => Category::Cashcard,
+            Some("gtopup") => Category::Gtopup,
+            Some("billpay") => Category::Billpay,
+            _ => return Err(StatusCode::InternalErrorIncomplete),
+        };
+
+        self.place_order(category, fields, fault).map(to_value)
+    }
+
+    fn balance(&self) -> Balance {
+        let ledger = self.ledger();
+
+        // รายการที่ยังไม่เสร็จถูกกันยอดไว้แล้ว แต่ยังไม่ถูกหักจากยอดคงเหลือทั้งหมด
+        let pending: Baht = ledger
+            .orders
+            .iter()
+            .filter(|o| o.status == TransactionStatus::InProgress)
+            .map(|o| o.total)
+            .sum();
+
+        Balance {
+            code: StatusCode::Success.as_str().to_string(),
+            ledger_balance: ledger.available + pending,
+            available_balance: ledger.available,
+        }
+    }
+
+    fn transaction(
+        &self,
+        transaction_id: Option<&str>,
+        dest_ref: Option<&str>,
+    ) -> Result<Transaction, StatusCode> {
+        let ledger = self.ledger();
+        let order = ledger
+            .orders
+            .iter()
+            .find(|o| match (transaction_id, dest_ref) {
+                (Some(id), _) => o.transaction_id == id,
+                (None, Some(dest_ref)) => o.dest_ref == dest_ref,
+                (None, None) => false,
+            })
+            .ok_or(StatusCode::TransactionNotFound)?;
+
+        Ok(Transaction {
+            code: StatusCode::Success.as_str().to_string(),
+            transaction_id: order.transaction_id.clone(),
+            dest_ref: order.dest_ref.clone(),
+            status: order.status,
+            operator_trxnsid: operator_trxnsid(order),
+            sms: sms(order),
+        })
+    }
+
+    /// ฟังก์ชันภายในสำหรับตรวจสอบคำสั่งซื้อ หักยอดเงิน และนัดส่ง callback
+    fn place_order(
+        self: &Arc<Self>,
+        category: Category,
+        fields: &HashMap<String, String>,
+        fault: Option<&Fault>,
+    ) -> Result<BillCommon, StatusCode> {
+        let field = |key| field(fields, key);
+        let dest_ref = field("dest_ref")
+            .filter(|v| DestRef::try_from(*v).is_ok())
+            .ok_or(StatusCode::InvalidCallbackRefId)?;
+        let resp_url = field("resp_url")
+            .filter(|v| CallbackUrl::try_from(*v).is_ok())
+            .ok_or(StatusCode::InvalidCallbackUrl)?;
+        let company = field("pay_to_company")
+            .and_then(|id| self.product.get(category, id))
+            .ok_or(StatusCode::InvalidCompany)?;
+        let amount = field("pay_to_amount")
+            .and_then(|v| v.parse::<Baht>().ok())
+            .filter(|amount| company.check_amount(*amount).is_ok())
+            .ok_or(StatusCode::InvalidPaymentAmount)?;
+        if category != Category::Cashcard && field("pay_to_ref1").is_none() {
+            return Err(StatusCode::InvalidRef1);
+        }
+
+        let total = amount + company.fee();
+        let bill = {
+            let mut ledger = self.ledger();
+            if ledger.orders.iter().any(|o| o.dest_ref == dest_ref) {
+                return Err(StatusCode::DuplicateCallbackRefId);
+            }
+            if ledger.available < total {
+                return Err(StatusCode::InsufficientFunds);
+            }
+
+            ledger.available -= total;
+            ledger.next_id += 1;
+            let order = SimulatedOrder {
+                transaction_id: ledger.next_id.to_string(),
+                dest_ref: dest_ref.to_string(),
+                category,
+                company: company.key().to_string(),
+                amount,
+                total,
+                resp_url: resp_url.to_string(),
+                status: TransactionStatus::InProgress,
+                acknowledged: false,
+            };
+            ledger.orders.push(order.clone());
+
+            BillCommon {
+                code: StatusCode::Success.as_str().to_string(),
+                bill_id: ledger.next_id,
+                transaction_id: order.transaction_id.clone(),
+                queue_id: ledger.next_id,
+                total_amount: total,
+                balance: ledger.available,
+            }
+        };
+
+        tokio::spawn(
+            self.clone()
+                .complete(bill.transaction_id.clone(), fault.cloned()),
+        );
+        Ok(bill)
+    }
+
+    /// ฟังก์ชันภายในสำหรับทำรายการให้เสร็จตาม `outcome` แล้ว POST callback ไปยัง `resp_url`
+    ///
+    /// `fault` กำหนดว่าจะส่ง callback ซ้ำ ผิดลำดับ หรือไม่ส่งเลย
+    async fn complete(self: Arc<Self>, transaction_id: String, fault: Option<Fault>) {
+        tokio::time::sleep(self.callback_delay).await;
+
+        let Some(order) = self.finish(&transaction_id) else {
+            return;
+        };
+
+        let mut form = vec![
+            ("transaction_id", order.transaction_id.clone()),
+            ("dest_ref", order.dest_ref.clone()),
+            ("status", order.status.as_str().to_string()),
+        ];
+        if order.status == TransactionStatus::Success {
+            form.push(("real_amount", order.amount.to_string()));
+        }
+        form.extend(operator_trxnsid(&order).map(|v| ("operator_trxnsid", v)));
+        form.extend(sms(&order).map(|v| ("sms", v)));
+
+        let callbacks = match fault {
+            Some(Fault::NoCallback) => Vec::new(),
+            Some(Fault::DuplicateCallback) => vec![form.clone(), form],
+            Some(Fault::OutOfOrderCallbacks) => {
+                let stale = vec![
+                    ("transaction_id", order.transaction_id.clone()),
+                    ("dest_ref", order.dest_ref.clone()),
+                    ("status", TransactionStatus::InProgress.as_str().to_string()),
+                ];
+                vec![form, stale]
+            }
+            _ => vec![form],
+        };
+
+        let ack = format!("SUCCEED|UPDATE_ID={}", order.transaction_id);
+        let mut acknowledged = false;
+        for form in &callbacks {
+            acknowledged |= match self.client.post(&order.resp_url).form(form).send().await {
+                Ok(response) if response.status().is_success() => {
+                    response.text().await.is_ok_and(|body| body.trim() == ack)
+                }
+                _ => false,
+            };
+        }
+
+        if let Some(order) = self
+            .ledger()
+            .orders
+            .iter_mut()
+            .find(|o| o.transaction_id == transaction_id)
+        {
+            order.acknowledged = acknowledged;
+        }
+    }
+
+    /// ฟังก์ชันภายในสำหรับเปลี่ยนสถานะรายการ (คืนเงินถ้าไม่สำเร็จ)
+    fn finish(&self, transaction_id: &str) -> Option<SimulatedOrder> {
+        let mut ledger = self.ledger();
+        let index = ledger
+            .orders
+            .iter()
+            .position(|o| o.transaction_id == transaction_id)?;
+
+        ledger.orders[index].status = self.outcome;
+        if self.outcome == TransactionStatus::Failed {
+            let total = ledger.orders[index].total;
+            ledger.available += total;
+        }
+        Some(ledger.orders[index].clone())
+    }
+}
+
+/// เลขอ้างอิงจากผู้ให้บริการปลายทางของรายการที่สำเร็จ
+fn operator_trxnsid(order: &SimulatedOrder) -> Option<String> {
+    (order.status == TransactionStatus::Success).then(|| format!("OP{}", order.transaction_id))
+}
+
+/// รหัส PIN ของบัตรเงินสดที่สำเร็จ
+fn sms(order: &SimulatedOrder) -> Option<String> {
+    (order.status == TransactionStatus::Success && order.category == Category::Cashcard)
+        .then(|| format!("PIN {:0>16}", order.transaction_id))
+}
+
+/// ค่าที่ไม่ว่างเปล่าของ `key` ใน form
+fn field<'a>(fields: &'a HashMap<String, String>, key: &str) -> Option<&'a str> {
+    fields.get(key).map(|v| v.trim()).filter(|v| !v.is_empty())
+}
+
+fn to_value(value: impl Serialize) -> serde_json::Value {
+    serde_json::to_value(value).expect("response is serializable")
+}
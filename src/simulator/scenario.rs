@@ -0,0 +1,239 @@
+use crate::catalog::Category;
+use crate::error::StatusCode;
+use crate::response::ErrorResponse;
+use axum::{
+    body::{Body, Bytes},
+    http::{self, header::CONTENT_TYPE},
+    response::{IntoResponse, Response},
+};
+use std::time::Duration;
+
+/// คำขอที่ `Fault` จะถูกนำไปใช้
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Any,             // ทุกคำขอ
+    Balance,         // ตรวจสอบยอดเงิน (balance_inquiry)
+    Transaction,     // ตรวจสอบสถานะรายการ (transaction_inquiry)
+    Orders,          // คำสั่งซื้อทุกประเภท
+    Order(Category), // คำสั่งซื้อประเภทที่กำหนด
+    Product,         // รายการสินค้า (comp_export.php)
+}
+
+impl Target {
+    /// ฟังก์ชันภายในสำหรับระบุ target ของคำขอไปยัง client_api จาก `type`
+    pub(super) fn of(order_type: Option<&str>) -> Self {
+        match order_type {
+            Some("balance_inquiry") => Target::Balance,
+            Some("transaction_inquiry") => Target::Transaction,
+            Some("mtopup") => Target::Order(Category::Mtopup),
+            Some("cashcard") => Target::Order(Category::Cashcard),
+            Some("gtopup") => Target::Order(Category::Gtopup),
+            Some("billpay") => Target::Order(Category::Billpay),
+            _ => Target::Any,
+        }
+    }
+
+    fn matches(&self, request: Target) -> bool {
+        match (self, request) {
+            (Target::Any, _) | (Target::Orders, Target::Order(_)) => true,
+            (target, request) => *target == request,
+        }
+    }
+}
+
+/// ความผิดปกติที่ simulator จำลองให้กับคำขอ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    // ตอบ error ของ wepay ด้วยรหัสที่กำหนด (HTTP 200 เช่นเดียวกับ wepay)
+    Error(StatusCode),
+    // ตอบด้วย HTTP status ที่กำหนด (body เป็น error ของ wepay ถ้าระบุ `code`)
+    Http {
+        status: u16,
+        code: Option<StatusCode>,
+    },
+    // ตอบเป็นหน้า HTML แทน JSON (เช่น หน้า error จาก proxy)
+    Html {
+        status: u16,
+    },
+    // ตอบด้วย body ที่กำหนดเอง
+    Body {
+        status: u16,
+        content_type: String,
+        body: String,
+    },
+    // หน่วงเวลาก่อนประมวลผลตามปกติ (เช่น ให้เกิน timeout ของ client)
+    Delay(Duration),
+    // ประมวลผลตามปกติ (รับคำสั่งซื้อแล้ว) แต่ตัด connection ก่อนส่ง body ครบ
+    DropConnection,
+    // ส่ง callback เดียวกันสองครั้ง
+    DuplicateCallback,
+    // ส่ง callback สถานะสุดท้ายก่อน แล้วตามด้วยสถานะ "อยู่ระหว่างทำรายการ" ที่ล้าสมัย
+    OutOfOrderCallbacks,
+    // ทำรายการเสร็จแต่ไม่ส่ง callback
+    NoCallback,
+}
+
+impl Fault {
+    /// ฟังก์ชันภายในสำหรับสร้าง response ที่ใช้แทนการประมวลผลปกติ (ถ้ามี)
+    pub(super) fn response(&self) -> Option<Response> {
+        let (status, content_type, body) = match self {
+            Fault::Error(code) => (200, "application/json", error_body(code)),
+            Fault::Http {
+                status,
+                code: Some(code),
+            } => (*status, "application/json", error_body(code)),
+            Fault::Http { status, code: None } => (*status, "text/plain", String::new()),
+            Fault::Html { status } => (
+                *status,
+                "text/html",
+                format!("<html><body><h1>{}</h1></body></html>", status),
+            ),
+            Fault::Body {
+                status,
+                content_type,
+                body,
+            } => (*status, content_type.as_str(), body.clone()),
+            _ => return None,
+        };
+
+        let status =
+            http::StatusCode::from_u16(status).unwrap_or(http::StatusCode::INTERNAL_SERVER_ERROR);
+        Some((status, [(CONTENT_TYPE, content_type.to_string())], body).into_response())
+    }
+
+    /// ฟังก์ชันภายในสำหรับสร้าง response ที่ส่ง header และ body บางส่วนแล้วตัด connection
+    pub(super) fn dropped() -> Response {
+        let chunks: [Result<Bytes, std::io::Error>; 2] = [
+            Ok(Bytes::from_static(b"{\"code\":")),
+            Err(std::io::Error::other("connection dropped by simulator")),
+        ];
+        (
+            [(CONTENT_TYPE, "application/json")],
+            Body::from_stream(futures_util::stream::iter(chunks)),
+        )
+            .into_response()
+    }
+}
+
+fn error_body(code: &StatusCode) -> String {
+    let error = ErrorResponse {
+        code: code.as_str().to_string(),
+        desc: super::describe(code).to_string(),
+    };
+    serde_json::to_string(&error).expect("response is serializable")
+}
+
+/// ลำดับความผิดปกติที่ simulator ใช้กับคำขอที่ได้รับ
+///
+/// แต่ละคำขอจะใช้ขั้นตอนแรกที่ `Target` ตรงกันและยังใช้ไม่ครบจำนวนครั้ง
+/// คำขอที่ไม่ตรงกับขั้นตอนใดเลยจะถูกประมวลผลตามปกติ
+///
+/// ```no_run
+/// # use wepay_rs::{error::StatusCode, simulator::{Fault, Scenario, Target}};
+/// let scenario = Scenario::new()
+///     .times(Target::Balance, Fault::Error(StatusCode::InternalErrorDb), 2)
+///     .once(Target::Orders, Fault::DropConnection);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Scenario {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone)]
+struct Step {
+    target: Target,
+    fault: Fault,
+    remaining: Option<usize>, // None คือใช้ได้ไม่จำกัด
+}
+
+impl Scenario {
+    /// สร้างลำดับที่ว่างเปล่า (ทุกคำขอถูกประมวลผลตามปกติ)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ใช้ `fault` กับคำขอถัดไปที่ตรงกับ `target` หนึ่งครั้ง
+    pub fn once(self, target: Target, fault: Fault) -> Self {
+        self.times(target, fault, 1)
+    }
+
+    /// ใช้ `fault` กับคำขอที่ตรงกับ `target` ตามจำนวนครั้งที่กำหนด
+    pub fn times(mut self, target: Target, fault: Fault, times: usize) -> Self {
+        self.steps.push(Step {
+            target,
+            fault,
+            remaining: Some(times),
+        });
+        self
+    }
+
+    /// ใช้ `fault` กับทุกคำขอที่ตรงกับ `target`
+    pub fn always(mut self, target: Target, fault: Fault) -> Self {
+        self.steps.push(Step {
+            target,
+            fault,
+            remaining: None,
+        });
+        self
+    }
+
+    /// ตรวจสอบว่าขั้นตอนที่มีจำนวนครั้งจำกัดถูกใช้ครบแล้วหรือไม่
+    pub fn is_finished(&self) -> bool {
+        self.steps
+            .iter()
+            .all(|step| step.remaining.is_none_or(|remaining| remaining == 0))
+    }
+
+    /// ฟังก์ชันภายในสำหรับเลือกความผิดปกติของคำขอถัดไป
+    pub(super) fn next(&mut self, request: Target) -> Option<Fault> {
+        let step = self.steps.iter_mut().find(|step| {
+            step.target.matches(request) && step.remaining.is_none_or(|remaining| remaining > 0)
+        })?;
+
+        if let Some(remaining) = &mut step.remaining {
+            *remaining -= 1;
+        }
+        Some(step.fault.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scenario_steps_in_order() {
+        let mut scenario = Scenario::new()
+            .once(Target::Balance, Fault::Error(StatusCode::InternalErrorDb))
+            .once(Target::Balance, Fault::Html { status: 502 })
+            .always(Target::Orders, Fault::NoCallback);
+
+        assert_eq!(
+            scenario.next(Target::Balance),
+            Some(Fault::Error(StatusCode::InternalErrorDb))
+        );
+        assert_eq!(scenario.next(Target::Product), None);
+        assert_eq!(
+            scenario.next(Target::Balance),
+            Some(Fault::Html { status: 502 })
+        );
+        assert_eq!(scenario.next(Target::Balance), None);
+        assert!(scenario.is_finished());
+
+        for _ in 0..3 {
+            assert_eq!(
+                scenario.next(Target::Order(Category::Cashcard)),
+                Some(Fault::NoCallback)
+            );
+        }
+    }
+
+    #[test]
+    fn test_target_matches() {
+        assert!(Target::Any.matches(Target::Product));
+        assert!(Target::Orders.matches(Target::Order(Category::Billpay)));
+        assert!(Target::Order(Category::Billpay).matches(Target::Order(Category::Billpay)));
+        assert!(!Target::Order(Category::Billpay).matches(Target::Order(Category::Mtopup)));
+        assert!(!Target::Orders.matches(Target::Balance));
+    }
+}
@@ -0,0 +1,267 @@
+use crate::error::WepayError;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+mod cassette;
+
+pub use cassette::{Cassette, Interaction};
+pub(crate) use cassette::{Recorder, Replayer};
+
+/// HTTP method ที่ wepay ใช้
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Method {
+    Get,
+    Post,
+}
+
+/// คำขอ HTTP ที่ส่งไปยัง wepay
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub form: Vec<(String, String)>, // ข้อมูล form ของ POST (ว่างสำหรับ GET)
+}
+
+impl HttpRequest {
+    /// สร้างคำขอ GET
+    pub fn get(url: impl Into<String>) -> Self {
+        HttpRequest {
+            method: Method::Get,
+            url: url.into(),
+            headers: Vec::new(),
+            form: Vec::new(),
+        }
+    }
+
+    /// สร้างคำขอ POST แบบ form-encoded
+    pub fn post(url: impl Into<String>, form: &[(&str, &str)]) -> Self {
+        HttpRequest {
+            method: Method::Post,
+            url: url.into(),
+            headers: Vec::new(),
+            form: form
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    /// เพิ่ม header
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// ค่าของข้อมูลใน form
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.form
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// response HTTP ที่ได้รับจาก wepay
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpResponse {
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl HttpResponse {
+    /// สร้าง response จาก HTTP status และ body (ไม่มี header)
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        HttpResponse {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    /// ค่าของ header (ไม่สนใจตัวพิมพ์เล็ก/ใหญ่ของชื่อ)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// ตรวจสอบว่า HTTP status อยู่ในช่วง 2xx หรือไม่
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Trait สำหรับส่งคำขอ HTTP ทุกคำขอของ `Wepay` (เช่น เพิ่ม middleware หรือใช้ HTTP client อื่น)
+///
+/// ค่าเริ่มต้นคือ `ReqwestTransport` และกำหนดเองได้ด้วย `WepayBuilder::transport`
+/// response ที่ไม่ใช่ 2xx ควรส่งคืนเป็น `HttpResponse` ตามปกติ เพื่อให้ `Wepay` แปลงเป็น `WepayError` เอง
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// ส่งคำขอและอ่าน response ทั้งหมด
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, WepayError>;
+}
+
+/// Transport ที่ส่งคำขอผ่าน `reqwest::Client` (ค่าเริ่มต้น)
+pub struct ReqwestTransport {
+    client: Client,
+}
+
+impl ReqwestTransport {
+    /// สร้าง transport จาก client ที่กำหนดค่าไว้แล้ว
+    pub fn new(client: Client) -> Self {
+        ReqwestTransport { client }
+    }
+}
+
+#[async_trait]
+impl Transport for ReqwestTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, WepayError> {
+        let mut builder = match request.method {
+            Method::Get => self.client.get(&request.url),
+            Method::Post => self.client.post(&request.url).form(&request.form),
+        };
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+
+        let response = builder.send().await?;
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+            })
+            .collect();
+        let body = response.bytes().await?;
+
+        Ok(HttpResponse {
+            status,
+            headers,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        })
+    }
+}
+
+/// Transport ในหน่วยความจำสำหรับ unit test ที่ตอบคำขอตามลำดับ response ที่เตรียมไว้โดยไม่ต้องเชื่อมต่อ
+///
+/// การ clone จะใช้คิวและรายการคำขอร่วมกัน จึงเก็บสำเนาไว้ตรวจสอบคำขอหลังส่งให้ `WepayBuilder::transport` ได้
+/// ```
+/// # use wepay_rs::{Wepay, money::Baht, transport::{HttpResponse, MemoryTransport}};
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), wepay_rs::error::WepayError> {
+/// let transport = MemoryTransport::new();
+/// transport.push(HttpResponse::new(
+///     200,
+///     r#"{"code":"00000","ledger_balance":"100.00","available_balance":"100.00"}"#,
+/// ));
+///
+/// let wepay = Wepay::builder()
+///     .username("username")
+///     .password("password")
+///     .transport(transport.clone())
+///     .build()?;
+/// let balance = wepay.balance().await?;
+/// assert_eq!(balance.available_balance, Baht::from_baht(100));
+/// assert_eq!(transport.requests()[0].field("type"), Some("balance_inquiry"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryTransport {
+    state: Arc<Mutex<MemoryState>>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    responses: VecDeque<HttpResponse>,
+    requests: Vec<HttpRequest>,
+}
+
+impl MemoryTransport {
+    /// สร้าง transport ที่ยังไม่มี response
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// เพิ่ม response ต่อท้ายคิว (คำขอแต่ละครั้งใช้ response แรกในคิว)
+    pub fn push(&self, response: HttpResponse) {
+        self.state().responses.push_back(response);
+    }
+
+    /// คำขอทั้งหมดที่ได้รับตามลำดับ
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.state().requests.clone()
+    }
+
+    /// จำนวน response ที่ยังไม่ถูกใช้
+    pub fn remaining(&self) -> usize {
+        self.state().responses.len()
+    }
+
+    fn state(&self) -> MutexGuard<'_, MemoryState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl Transport for MemoryTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, WepayError> {
+        let mut state = self.state();
+        let response = state.responses.pop_front().ok_or_else(|| {
+            let message = format!(
+                "no response queued for {:?} {} (type={})",
+                request.method,
+                request.url,
+                request.field("type").unwrap_or("-")
+            );
+            WepayError::Transport(message.into())
+        });
+        state.requests.push(request);
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_transport_responds_in_order() {
+        let transport = MemoryTransport::new();
+        transport.push(HttpResponse::new(200, "first"));
+        transport.push(HttpResponse::new(502, "second"));
+
+        let handle = transport.clone();
+        let send = |kind: &str| {
+            transport.send(HttpRequest::post(
+                "https://www.wepay.in.th/client_api.json.php",
+                &[("type", kind)],
+            ))
+        };
+        assert_eq!(send("balance_inquiry").await.unwrap().body, "first");
+        assert_eq!(send("mtopup").await.unwrap().status, 502);
+        assert!(matches!(
+            send("balance_inquiry").await,
+            Err(WepayError::Transport(_))
+        ));
+
+        let kinds: Vec<_> = handle
+            .requests()
+            .iter()
+            .map(|r| r.field("type").unwrap().to_string())
+            .collect();
+        assert_eq!(kinds, ["balance_inquiry", "mtopup", "balance_inquiry"]);
+        assert_eq!(handle.remaining(), 0);
+    }
+}
@@ -0,0 +1,337 @@
+use super::{HttpRequest, HttpResponse, Transport};
+use crate::error::WepayError;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// ข้อมูลใน form ที่ถูกปิดบังก่อนบันทึกลง cassette
+const REDACTED_FIELDS: [&str; 2] = ["username", "password"];
+const REDACTED: &str = "[REDACTED]";
+
+/// header ของคำขอและ response ที่ถูกตัดออกก่อนบันทึกลง cassette
+const STRIPPED_HEADERS: [&str; 4] = [
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+];
+
+/// คำขอและ response ที่บันทึกไว้หนึ่งคู่
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: HttpRequest,
+    pub response: HttpResponse,
+}
+
+/// ไฟล์บันทึกคำขอและ response ที่ส่งไปยัง wepay (ปิดบัง username/password แล้ว)
+///
+/// ใช้ `WepayBuilder::record` เพื่อบันทึก และ `WepayBuilder::replay` เพื่อตอบคำขอจากไฟล์โดยไม่ต้องเชื่อมต่อ
+/// ```no_run
+/// # use wepay_rs::{Wepay, transport::Cassette};
+/// # fn run() -> Result<(), wepay_rs::error::WepayError> {
+/// let wepay = Wepay::builder()
+///     .replay(Cassette::load("tests/cassettes/balance.json")?)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    /// อ่าน cassette จากไฟล์ JSON
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, WepayError> {
+        let json = std::fs::read_to_string(path).map_err(|e| WepayError::Transport(e.into()))?;
+        serde_json::from_str(&json).map_err(|e| WepayError::Transport(e.into()))
+    }
+
+    /// บันทึก cassette ลงไฟล์ JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), WepayError> {
+        let json = serde_json::to_string_pretty(self).expect("cassette is serializable");
+        std::fs::write(path, json).map_err(|e| WepayError::Transport(e.into()))
+    }
+}
+
+/// ฟังก์ชันภายในสำหรับปิดบังข้อมูลรับรองในคำขอ
+fn redact(mut request: HttpRequest) -> HttpRequest {
+    for (name, value) in &mut request.form {
+        if REDACTED_FIELDS.contains(&name.as_str()) {
+            *value = REDACTED.to_string();
+        }
+    }
+    strip_headers(&mut request.headers);
+    request
+}
+
+/// ฟังก์ชันภายในสำหรับตัด header ที่มีข้อมูลรับรองหรือ session ออก
+fn strip_headers(headers: &mut Vec<(String, String)>) {
+    headers.retain(|(name, _)| {
+        !STRIPPED_HEADERS
+            .iter()
+            .any(|stripped| name.eq_ignore_ascii_case(stripped))
+    });
+}
+
+/// ฟังก์ชันภายในสำหรับเปรียบเทียบคำขอกับคำขอที่บันทึกไว้
+///
+/// เทียบเฉพาะ endpoint ท้าย URL (เพื่อให้ใช้ cassette กับ base_url อื่นได้) และไม่สนใจข้อมูลที่ถูกปิดบัง
+fn matches(recorded: &HttpRequest, request: &HttpRequest) -> bool {
+    let endpoint = |url: &str| match url::Url::parse(url) {
+        Ok(url) => {
+            let name = url.path_segments().and_then(|mut s| s.next_back());
+            format!(
+                "{}?{}",
+                name.unwrap_or_default(),
+                url.query().unwrap_or_default()
+            )
+        }
+        Err(_) => url.to_string(),
+    };
+    let form = |request: &HttpRequest| {
+        request
+            .form
+            .iter()
+            .filter(|(name, _)| !REDACTED_FIELDS.contains(&name.as_str()))
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    recorded.method == request.method
+        && endpoint(&recorded.url) == endpoint(&request.url)
+        && form(recorded) == form(request)
+}
+
+/// Transport ภายในที่ส่งคำขอผ่าน transport ที่ใช้งานอยู่และเก็บทุกคู่ไว้ในหน่วยความจำ
+///
+/// cassette จะถูกเขียนลงไฟล์เมื่อเรียก `flush` หรือเมื่อ `Wepay` ตัวสุดท้ายถูก drop
+/// การเขียนไฟล์ไม่มีผลต่อผลลัพธ์ของคำขอ
+pub(crate) struct Recorder {
+    inner: Arc<dyn Transport>,
+    path: PathBuf,
+    state: Mutex<Recording>,
+    writing: tokio::sync::Mutex<()>, // ป้องกันการเขียนไฟล์ซ้อนกัน (ไฟล์เก่าทับไฟล์ใหม่)
+}
+
+#[derive(Default)]
+struct Recording {
+    cassette: Cassette,
+    dirty: bool, // มีคู่ที่ยังไม่ได้เขียนลงไฟล์
+}
+
+impl Recorder {
+    pub(crate) fn new(inner: Arc<dyn Transport>, path: PathBuf) -> Self {
+        Recorder {
+            inner,
+            path,
+            state: Mutex::new(Recording::default()),
+            writing: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, Recording> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// เขียนคู่ที่บันทึกไว้ทั้งหมดลงไฟล์ (ใน thread แยกเพื่อไม่ให้ runtime หยุดรอ)
+    pub(crate) async fn flush(&self) -> Result<(), WepayError> {
+        let _guard = self.writing.lock().await;
+        let cassette = {
+            let mut state = self.state();
+            if !state.dirty {
+                return Ok(());
+            }
+            state.dirty = false;
+            state.cassette.clone()
+        };
+
+        let path = self.path.clone();
+        let result = tokio::task::spawn_blocking(move || cassette.save(path))
+            .await
+            .map_err(|e| WepayError::Transport(e.into()))
+            .and_then(|result| result);
+
+        // เขียนไม่สำเร็จ ให้ลองใหม่ในครั้งถัดไป
+        if result.is_err() {
+            self.state().dirty = true;
+        }
+        result
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        let state = self.state.get_mut().unwrap_or_else(|e| e.into_inner());
+        if state.dirty {
+            // ไม่มีผู้รับ error แล้ว ใช้ `Wepay::flush_recording` ถ้าต้องการทราบผล
+            let _ = state.cassette.save(&self.path);
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for Recorder {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, WepayError> {
+        let recorded = redact(request.clone());
+        let response = self.inner.send(request).await?;
+
+        let mut stored = response.clone();
+        strip_headers(&mut stored.headers);
+
+        let mut state = self.state();
+        state.cassette.interactions.push(Interaction {
+            request: recorded,
+            response: stored,
+        });
+        state.dirty = true;
+        Ok(response)
+    }
+}
+
+/// Transport ภายในที่ตอบคำขอจาก cassette ตามลำดับที่บันทึกไว้ (แต่ละคู่ใช้ได้ครั้งเดียว)
+pub(crate) struct Replayer {
+    interactions: Mutex<Vec<Option<Interaction>>>,
+}
+
+impl Replayer {
+    pub(crate) fn new(cassette: Cassette) -> Self {
+        Replayer {
+            interactions: Mutex::new(cassette.interactions.into_iter().map(Some).collect()),
+        }
+    }
+
+    fn interactions(&self) -> MutexGuard<'_, Vec<Option<Interaction>>> {
+        self.interactions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl Transport for Replayer {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, WepayError> {
+        let mut interactions = self.interactions();
+        let interaction = interactions
+            .iter_mut()
+            .find(|i| i.as_ref().is_some_and(|i| matches(&i.request, &request)))
+            .and_then(Option::take)
+            .ok_or_else(|| {
+                let message = format!(
+                    "no recorded response for {:?} {} (type={})",
+                    request.method,
+                    request.url,
+                    request.field("type").unwrap_or("-")
+                );
+                WepayError::Transport(message.into())
+            })?;
+        Ok(interaction.response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MemoryTransport;
+
+    fn balance_request(password: &str) -> HttpRequest {
+        HttpRequest::post(
+            "https://www.wepay.in.th/client_api.json.php",
+            &[
+                ("username", "user"),
+                ("password", password),
+                ("type", "balance_inquiry"),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_redact_credentials() {
+        let request = redact(balance_request("secret"));
+        assert_eq!(request.field("username"), Some(REDACTED));
+        assert_eq!(request.field("password"), Some(REDACTED));
+        assert_eq!(request.field("type"), Some("balance_inquiry"));
+    }
+
+    #[test]
+    fn test_matches_ignores_base_url_and_credentials() {
+        let recorded = redact(balance_request("secret"));
+        let mut request = balance_request("other");
+        request.url = "http://127.0.0.1:1234/proxy/client_api.json.php".to_string();
+        assert!(matches(&recorded, &request));
+
+        let product = HttpRequest::get("https://www.wepay.in.th/comp_export.php?json");
+        assert!(!matches(&recorded, &product));
+        assert!(matches(
+            &product,
+            &HttpRequest::get("http://localhost/comp_export.php?json")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_record_strips_headers_and_writes_on_flush() {
+        let transport = MemoryTransport::new();
+        transport.push(HttpResponse {
+            status: 200,
+            headers: vec![
+                ("Set-Cookie".to_string(), "PHPSESSID=secret".to_string()),
+                ("content-type".to_string(), "application/json".to_string()),
+            ],
+            body: "{}".to_string(),
+        });
+        let path = std::env::temp_dir().join(format!("wepay-recorder-{}.json", std::process::id()));
+        let recorder = Recorder::new(Arc::new(transport), path.clone());
+
+        let request = balance_request("secret").header("Authorization", "Basic secret");
+        let response = recorder.send(request).await.unwrap();
+        assert_eq!(response.header("set-cookie"), Some("PHPSESSID=secret"));
+        assert!(!path.exists());
+
+        recorder.flush().await.unwrap();
+        let json = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(!json.contains("secret"));
+        assert!(json.contains("content-type"));
+    }
+
+    #[tokio::test]
+    async fn test_record_write_failure_does_not_fail_request() {
+        let transport = MemoryTransport::new();
+        transport.push(HttpResponse::new(200, "{}"));
+        let path = std::env::temp_dir()
+            .join("wepay-missing-dir")
+            .join("cassette.json");
+        let recorder = Recorder::new(Arc::new(transport), path);
+
+        assert!(recorder.send(balance_request("secret")).await.is_ok());
+        assert!(matches!(
+            recorder.flush().await,
+            Err(WepayError::Transport(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_replay_each_interaction_once() {
+        let response = |body: &str| HttpResponse {
+            status: 200,
+            headers: Vec::new(),
+            body: body.to_string(),
+        };
+        let replayer = Replayer::new(Cassette {
+            interactions: vec![
+                Interaction {
+                    request: redact(balance_request("secret")),
+                    response: response("first"),
+                },
+                Interaction {
+                    request: redact(balance_request("secret")),
+                    response: response("second"),
+                },
+            ],
+        });
+
+        let send = || replayer.send(balance_request("secret"));
+        assert_eq!(send().await.unwrap().body, "first");
+        assert_eq!(send().await.unwrap().body, "second");
+        assert!(matches!(send().await, Err(WepayError::Transport(_))));
+    }
+}
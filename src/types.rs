@@ -0,0 +1,280 @@
+use crate::error::{Rule, ValidationError, WepayError};
+use crate::validator;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use url::{Host, Url};
+
+/// Trait ภายในสำหรับแปลงสตริงเป็น newtype (ล้มเหลวได้ด้วย `ValidationError` เท่านั้น)
+pub(crate) trait Parse: Sized {
+    fn parse(value: &str) -> Result<Self, ValidationError>;
+}
+
+/// ค่าที่ builder ของคำสั่งซื้อรับแทน newtype `T` ได้
+///
+/// newtype ที่ผ่านการตรวจสอบแล้ว (`T` หรือ `&T`) จะถูกใช้ตามเดิมโดยไม่ตรวจสอบซ้ำ
+/// ส่วนสตริง (`&str`, `String`) จะถูกตรวจสอบและแปลงเป็น `T`
+pub trait IntoNewtype<T> {
+    /// แปลงเป็น newtype `T` (หรือคืนเหตุผลที่ข้อมูลไม่ผ่านการตรวจสอบ)
+    fn into_newtype(self) -> Result<T, ValidationError>;
+}
+
+/// dest_ref ที่ผ่านการตรวจสอบแล้ว (a-z, A-Z, 0-9 ความยาว 1-20 ตัวอักษร)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DestRef(String);
+
+impl DestRef {
+    /// dest_ref ในรูปแบบสตริง
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Parse for DestRef {
+    fn parse(value: &str) -> Result<Self, ValidationError> {
+        let invalid = |rule| ValidationError::new("dest_ref", rule).with_value(value);
+
+        if value.is_empty() {
+            return Err(invalid(Rule::Empty));
+        }
+        validator::check_ref(value).map_err(invalid)?;
+        Ok(DestRef(value.to_string()))
+    }
+}
+
+/// เบอร์มือถือไทยที่ผ่านการตรวจสอบแล้ว เก็บเฉพาะตัวเลข 10 หลัก (เช่น `081-234-5678` เป็น `0812345678`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct MobileNumber(String);
+
+impl MobileNumber {
+    /// เบอร์มือถือในรูปแบบตัวเลขล้วน
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Parse for MobileNumber {
+    fn parse(value: &str) -> Result<Self, ValidationError> {
+        validator::check_thai_mobile_number(value).map_err(|rule| {
+            ValidationError::new("target or pay_to_ref1", rule).with_redacted_value(value)
+        })?;
+
+        // ลบตัวคั่น เช่น ขีดหรือช่องว่าง ก่อนส่งไปยัง wepay
+        Ok(MobileNumber(
+            value.chars().filter(|c| c.is_ascii_digit()).collect(),
+        ))
+    }
+}
+
+/// URL สำหรับรับ callback (`resp_url`) ที่ผ่านการตรวจสอบแล้ว
+///
+/// ต้องเป็น https ยกเว้น URL ที่ชี้ไปยังเครื่องตัวเอง (`localhost`, `127.0.0.1`, `::1`) ซึ่งใช้ http ได้
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CallbackUrl(Url);
+
+impl CallbackUrl {
+    /// URL ในรูปแบบสตริง
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// URL ในรูปแบบ `url::Url`
+    pub fn as_url(&self) -> &Url {
+        &self.0
+    }
+}
+
+impl Parse for CallbackUrl {
+    fn parse(value: &str) -> Result<Self, ValidationError> {
+        let invalid = |rule| ValidationError::new("callback or resp_url", rule).with_value(value);
+
+        let url = validator::check_url(value).map_err(invalid)?;
+
+        // wepay ส่ง callback ผ่าน http/https เท่านั้น
+        match url.scheme() {
+            "https" => {}
+            // http อนุญาตเฉพาะ loopback สำหรับทดสอบในเครื่อง (เช่น simulator)
+            "http" if is_loopback(&url) => {}
+            "http" => return Err(invalid(Rule::NotHttps)),
+            scheme => return Err(invalid(Rule::UnsupportedScheme(scheme.to_string()))),
+        }
+        Ok(CallbackUrl(url))
+    }
+}
+
+/// ฟังก์ชันภายในสำหรับตรวจสอบว่า URL ชี้ไปยังเครื่องตัวเอง
+fn is_loopback(url: &Url) -> bool {
+    match url.host() {
+        Some(Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+// implement trait ที่เหมือนกันของทุก newtype
+macro_rules! impl_string_newtype {
+    ($($name:ident),*) => {
+        $(
+            impl TryFrom<&str> for $name {
+                type Error = WepayError;
+
+                fn try_from(value: &str) -> Result<Self, Self::Error> {
+                    Ok(<Self as Parse>::parse(value)?)
+                }
+            }
+
+            impl IntoNewtype<$name> for $name {
+                fn into_newtype(self) -> Result<$name, ValidationError> {
+                    Ok(self)
+                }
+            }
+
+            impl IntoNewtype<$name> for &$name {
+                fn into_newtype(self) -> Result<$name, ValidationError> {
+                    Ok(self.clone())
+                }
+            }
+
+            impl IntoNewtype<$name> for &str {
+                fn into_newtype(self) -> Result<$name, ValidationError> {
+                    <$name as Parse>::parse(self)
+                }
+            }
+
+            impl IntoNewtype<$name> for String {
+                fn into_newtype(self) -> Result<$name, ValidationError> {
+                    <$name as Parse>::parse(&self)
+                }
+            }
+
+            impl IntoNewtype<$name> for &String {
+                fn into_newtype(self) -> Result<$name, ValidationError> {
+                    <$name as Parse>::parse(self)
+                }
+            }
+
+            impl TryFrom<String> for $name {
+                type Error = WepayError;
+
+                fn try_from(value: String) -> Result<Self, Self::Error> {
+                    Self::try_from(value.as_str())
+                }
+            }
+
+            impl FromStr for $name {
+                type Err = WepayError;
+
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    Self::try_from(s)
+                }
+            }
+
+            impl From<$name> for String {
+                fn from(value: $name) -> String {
+                    value.as_str().to_string()
+                }
+            }
+
+            impl AsRef<str> for $name {
+                fn as_ref(&self) -> &str {
+                    self.as_str()
+                }
+            }
+
+            impl fmt::Display for $name {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.write_str(self.as_str())
+                }
+            }
+        )*
+    };
+}
+
+impl_string_newtype!(DestRef, MobileNumber, CallbackUrl);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dest_ref() {
+        let dest_ref = DestRef::try_from("TEST0000000000000001").unwrap();
+        assert_eq!(dest_ref.as_str(), "TEST0000000000000001");
+        assert_eq!(dest_ref.to_string(), "TEST0000000000000001");
+
+        assert!(DestRef::try_from("").is_err()); // ต้องไม่ว่างเปล่า
+        assert!(DestRef::try_from("under_score").is_err());
+        assert!(DestRef::try_from("123456789012345678901").is_err());
+    }
+
+    #[test]
+    fn test_mobile_number_normalization() {
+        let number = MobileNumber::try_from("081-234-5678").unwrap();
+        assert_eq!(number.as_str(), "0812345678");
+
+        let number: MobileNumber = "081 234 5678".parse().unwrap();
+        assert_eq!(number.as_str(), "0812345678");
+
+        assert!(MobileNumber::try_from("0212345678").is_err());
+    }
+
+    #[test]
+    fn test_callback_url() {
+        let url = CallbackUrl::try_from("https://www.mywebsite.com/wepay_result.php").unwrap();
+        assert_eq!(url.as_str(), "https://www.mywebsite.com/wepay_result.php");
+        assert_eq!(url.as_url().host_str(), Some("www.mywebsite.com"));
+
+        assert!(CallbackUrl::try_from("invalid-url").is_err());
+        assert!(CallbackUrl::try_from("ftp://ftp.example.com").is_err());
+
+        // http ใช้ได้เฉพาะ loopback
+        assert!(CallbackUrl::try_from("http://localhost:8080/wepay_result").is_ok());
+        assert!(CallbackUrl::try_from("http://127.0.0.1:8080/wepay_result").is_ok());
+        assert!(CallbackUrl::try_from("http://[::1]/wepay_result").is_ok());
+        let error = CallbackUrl::try_from("http://www.mywebsite.com/wepay_result.php").unwrap_err();
+        let error = error
+            .validation()
+            .unwrap()
+            .get("callback or resp_url")
+            .unwrap();
+        assert_eq!(error.rule, Rule::NotHttps);
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let error = DestRef::try_from("under_score").unwrap_err();
+        let error = error.validation().unwrap().get("dest_ref").unwrap();
+        assert_eq!(error.rule, Rule::InvalidCharacter('_'));
+        assert_eq!(error.value.as_deref(), Some("under_score"));
+
+        // เบอร์มือถือเป็นข้อมูลส่วนตัว จึงแสดงเฉพาะ 4 ตัวสุดท้าย
+        let error = MobileNumber::try_from("021-234-5678").unwrap_err();
+        let error = error
+            .validation()
+            .unwrap()
+            .get("target or pay_to_ref1")
+            .unwrap();
+        assert_eq!(error.rule, Rule::BadPrefix);
+        assert_eq!(error.value.as_deref(), Some("********5678"));
+
+        let error = CallbackUrl::try_from("ftp://ftp.example.com").unwrap_err();
+        let error = error
+            .validation()
+            .unwrap()
+            .get("callback or resp_url")
+            .unwrap();
+        assert_eq!(error.rule, Rule::UnsupportedScheme("ftp".into()));
+    }
+
+    #[test]
+    fn test_serde() {
+        let number: MobileNumber = serde_json::from_str(r#""081-234-5678""#).unwrap();
+        assert_eq!(serde_json::to_string(&number).unwrap(), r#""0812345678""#);
+
+        assert!(serde_json::from_str::<DestRef>(r#""under_score""#).is_err());
+    }
+}
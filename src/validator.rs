@@ -1,33 +1,48 @@
+use crate::error::Rule;
+use regex::Regex;
 use url::Url;
 
-/// ตรวจสอบความถูกต้องของ dest_ref (อ้างอิงจาก doc ของ wepay)
-pub fn is_valid_ref(s: &str) -> bool {
+/// ตรวจสอบความถูกต้องของ dest_ref (อ้างอิงจาก doc ของ wepay) และคืนกฎข้อแรกที่ไม่ผ่าน
+pub fn check_ref(s: &str) -> Result<(), Rule> {
     // ตรวจสอบความยาว (max 20 characters)
     if s.len() > 20 {
-        return false;
-    }
-    // ตรวจสอบอักขระแต่ละตัว
-    s.chars().all(|c| {
-        c.is_ascii_lowercase() ||  // a-z
-        c.is_ascii_uppercase() ||  // A-Z
-        c.is_ascii_digit() // 0-9
-    })
+        return Err(Rule::TooLong { max: 20 });
+    }
+    // ตรวจสอบอักขระแต่ละตัว (a-z, A-Z, 0-9 เท่านั้น)
+    match s.chars().find(|c| !c.is_ascii_alphanumeric()) {
+        Some(c) => Err(Rule::InvalidCharacter(c)),
+        None => Ok(()),
+    }
 }
 
-/// ตรวจสอบความถูกต้องของเบอร์มือถือไทย (06, 08 and 09 Only)
-pub fn is_thai_mobile_number(number: &str) -> bool {
+/// ตรวจสอบความถูกต้องของเบอร์มือถือไทย (06, 08 and 09 Only) และคืนกฎข้อแรกที่ไม่ผ่าน
+pub fn check_thai_mobile_number(number: &str) -> Result<(), Rule> {
     // ทำความสะอาดตัวเลขโดยลบตัวอักษรที่ไม่ใช่ตัวเลขทั้งหมด
     let cleaned: String = number.chars().filter(|c| c.is_ascii_digit()).collect();
 
     // ตรวจสอบว่าเบอร์ที่ทำความสะอาดแล้วเป็นเบอร์มือถือไทยที่ถูกต้องหรือไม่
-    cleaned.len() == 10
-        && cleaned.starts_with('0')
-        && matches!(cleaned.chars().nth(1), Some('6') | Some('8') | Some('9'))
+    if cleaned.len() != 10 {
+        return Err(Rule::BadLength { expected: 10 });
+    }
+    if !(cleaned.starts_with('0')
+        && matches!(cleaned.chars().nth(1), Some('6') | Some('8') | Some('9')))
+    {
+        return Err(Rule::BadPrefix);
+    }
+    Ok(())
 }
 
-/// ตรวจสอบความถูกต้องของลิงค์
-pub fn is_valid_url(url: &str) -> bool {
-    Url::parse(url).is_ok()
+/// ตรวจสอบความถูกต้องของลิงค์และคืน URL ที่แปลงแล้ว
+pub fn check_url(url: &str) -> Result<Url, Rule> {
+    Url::parse(url).map_err(|_| Rule::NotUrl)
+}
+
+/// ตรวจสอบค่าตามรูปแบบ (regex) ที่ได้จาก `refs_format` ของ wepay ทั้งสตริง
+///
+/// คืนค่า `None` ถ้ารูปแบบที่ได้รับไม่ใช่ regex ที่ถูกต้อง
+pub fn matches_format(value: &str, pattern: &str) -> Option<bool> {
+    let re = Regex::new(&format!("^(?:{})$", pattern)).ok()?;
+    Some(re.is_match(value))
 }
 
 #[cfg(test)]
@@ -36,111 +51,138 @@ mod tests {
 
     #[test]
     fn test_valid_ref() {
-        assert!(is_valid_ref("abcABC123"));
-        assert!(is_valid_ref("ValidString123"));
-        assert!(is_valid_ref("1234567890"));
-        assert!(is_valid_ref("a"));
-        assert!(is_valid_ref("A"));
-        assert!(is_valid_ref("0"));
-        assert!(is_valid_ref("")); // empty string
-        assert!(is_valid_ref("aBcD1234eFgH5678")); // exactly 16 chars
+        assert!(check_ref("abcABC123").is_ok());
+        assert!(check_ref("ValidString123").is_ok());
+        assert!(check_ref("1234567890").is_ok());
+        assert!(check_ref("a").is_ok());
+        assert!(check_ref("A").is_ok());
+        assert!(check_ref("0").is_ok());
+        assert!(check_ref("").is_ok()); // empty string
+        assert!(check_ref("aBcD1234eFgH5678").is_ok()); // exactly 16 chars
     }
 
     #[test]
     fn test_is_valid_ref_max_length() {
-        assert!(is_valid_ref("12345678901234567890")); // exactly 20 chars
-        assert!(!is_valid_ref("123456789012345678901")); // 21 chars
-        assert!(!is_valid_ref("ThisStringIsWayTooLongForTheLimit")); // 32 chars
+        assert!(check_ref("12345678901234567890").is_ok()); // exactly 20 chars
+        assert!(check_ref("123456789012345678901").is_err()); // 21 chars
+        assert!(check_ref("ThisStringIsWayTooLongForTheLimit").is_err()); // 32 chars
     }
 
     #[test]
     fn test_is_valid_ref_invalid_characters() {
-        assert!(!is_valid_ref("invalid!char"));
-        assert!(!is_valid_ref("space in string"));
-        assert!(!is_valid_ref("under_score"));
-        assert!(!is_valid_ref("dash-included"));
-        assert!(!is_valid_ref("日本語")); // non-ASCII
-        assert!(!is_valid_ref("emoji😊")); // emoji
-        assert!(!is_valid_ref("new\nline")); // control character
+        assert!(check_ref("invalid!char").is_err());
+        assert!(check_ref("space in string").is_err());
+        assert!(check_ref("under_score").is_err());
+        assert!(check_ref("dash-included").is_err());
+        assert!(check_ref("日本語").is_err()); // non-ASCII
+        assert!(check_ref("emoji😊").is_err()); // emoji
+        assert!(check_ref("new\nline").is_err()); // control character
     }
 
     #[test]
     fn test_is_valid_ref_edge_cases() {
-        assert!(is_valid_ref(&"a".repeat(20))); // max length with single char
-        assert!(is_valid_ref(&"A".repeat(20))); // max length with single char
-        assert!(is_valid_ref(&"0".repeat(20))); // max length with single char
-        assert!(!is_valid_ref(&"a".repeat(21))); // just over limit
-        assert!(is_valid_ref(&("aB1".repeat(6) + "aB"))); // 20 chars mixed
+        assert!(check_ref(&"a".repeat(20)).is_ok()); // max length with single char
+        assert!(check_ref(&"A".repeat(20)).is_ok()); // max length with single char
+        assert!(check_ref(&"0".repeat(20)).is_ok()); // max length with single char
+        assert!(check_ref(&"a".repeat(21)).is_err()); // just over limit
+        assert!(check_ref(&("aB1".repeat(6) + "aB")).is_ok()); // 20 chars mixed
+    }
+
+    #[test]
+    fn test_check_ref_rules() {
+        assert_eq!(
+            check_ref("123456789012345678901"),
+            Err(Rule::TooLong { max: 20 })
+        );
+        assert_eq!(check_ref("under_score"), Err(Rule::InvalidCharacter('_')));
     }
 
     #[test]
     fn test_valid_thai_numbers() {
         // AIS numbers
-        assert!(is_thai_mobile_number("0812345678"));
-        assert!(is_thai_mobile_number("0898765432"));
+        assert!(check_thai_mobile_number("0812345678").is_ok());
+        assert!(check_thai_mobile_number("0898765432").is_ok());
 
         // DTAC numbers
-        assert!(is_thai_mobile_number("0612345678"));
-        assert!(is_thai_mobile_number("0667890123"));
+        assert!(check_thai_mobile_number("0612345678").is_ok());
+        assert!(check_thai_mobile_number("0667890123").is_ok());
 
         // TrueMove numbers
-        assert!(is_thai_mobile_number("0912345678"));
-        assert!(is_thai_mobile_number("0998765432"));
+        assert!(check_thai_mobile_number("0912345678").is_ok());
+        assert!(check_thai_mobile_number("0998765432").is_ok());
 
         // With formatting
-        assert!(is_thai_mobile_number("081-234-5678"));
-        assert!(is_thai_mobile_number("081 234 5678"));
+        assert!(check_thai_mobile_number("081-234-5678").is_ok());
+        assert!(check_thai_mobile_number("081 234 5678").is_ok());
     }
 
     #[test]
     fn test_invalid_thai_numbers() {
         // Wrong length
-        assert!(!is_thai_mobile_number("081234567")); // too short
-        assert!(!is_thai_mobile_number("08123456789")); // too long
+        assert!(check_thai_mobile_number("081234567").is_err()); // too short
+        assert!(check_thai_mobile_number("08123456789").is_err()); // too long
 
         // Wrong prefix
-        assert!(!is_thai_mobile_number("0212345678")); // Bangkok landline
-        assert!(!is_thai_mobile_number("0312345678")); // Nonthaburi landline
-        assert!(!is_thai_mobile_number("0712345678")); // Invalid mobile prefix
+        assert!(check_thai_mobile_number("0212345678").is_err()); // Bangkok landline
+        assert!(check_thai_mobile_number("0312345678").is_err()); // Nonthaburi landline
+        assert!(check_thai_mobile_number("0712345678").is_err()); // Invalid mobile prefix
 
         // International numbers
-        assert!(!is_thai_mobile_number("+1212345678")); // US number
-        assert!(!is_thai_mobile_number("+441234567890")); // UK number
+        assert!(check_thai_mobile_number("+1212345678").is_err()); // US number
+        assert!(check_thai_mobile_number("+441234567890").is_err()); // UK number
 
         // Non-numeric
-        assert!(!is_thai_mobile_number("08ABCDEFGH"));
-        assert!(!is_thai_mobile_number("phone number"));
+        assert!(check_thai_mobile_number("08ABCDEFGH").is_err());
+        assert!(check_thai_mobile_number("phone number").is_err());
+    }
+
+    #[test]
+    fn test_check_thai_mobile_number_rules() {
+        assert_eq!(
+            check_thai_mobile_number("081234567"),
+            Err(Rule::BadLength { expected: 10 })
+        );
+        assert_eq!(check_thai_mobile_number("0212345678"), Err(Rule::BadPrefix));
     }
 
     #[test]
     fn test_thai_numbers_edge_cases() {
-        assert!(!is_thai_mobile_number("")); // empty string
-        assert!(!is_thai_mobile_number("0000000000")); // all zeros
-        assert!(!is_thai_mobile_number("9999999999")); // all nines
-        assert!(!is_thai_mobile_number("          ")); // spaces
+        assert!(check_thai_mobile_number("").is_err()); // empty string
+        assert!(check_thai_mobile_number("0000000000").is_err()); // all zeros
+        assert!(check_thai_mobile_number("9999999999").is_err()); // all nines
+        assert!(check_thai_mobile_number("          ").is_err()); // spaces
+    }
+
+    #[test]
+    fn test_matches_format() {
+        assert_eq!(matches_format("123456789", "[0-9]{5,10}"), Some(true));
+        assert_eq!(matches_format("1234", "[0-9]{5,10}"), Some(false));
+        assert_eq!(matches_format("12345abc", "[0-9]{5,10}"), Some(false)); // ต้องตรงทั้งสตริง
+        assert_eq!(matches_format("12345", "^[0-9]+$"), Some(true)); // รูปแบบที่มี anchor อยู่แล้ว
+        assert_eq!(matches_format("12345", "[0-9"), None); // regex ไม่ถูกต้อง
     }
 
     #[test]
     fn test_valid_urls() {
         // URLs ที่คาดว่าจะเป็น valid
-        assert!(is_valid_url("https://example.com"));
-        assert!(is_valid_url("http://localhost:8080"));
-        assert!(is_valid_url("ftp://ftp.example.com"));
-        assert!(is_valid_url("https://sub.example.com/path?query=value"));
+        assert!(check_url("https://example.com").is_ok());
+        assert!(check_url("http://localhost:8080").is_ok());
+        assert!(check_url("ftp://ftp.example.com").is_ok());
+        assert!(check_url("https://sub.example.com/path?query=value").is_ok());
     }
 
     #[test]
     fn test_invalid_urls() {
         // URLs ที่คาดว่าจะเป็น invalid
-        assert!(!is_valid_url("invalid-url"));
-        assert!(!is_valid_url("https://"));
-        assert!(!is_valid_url("ftp://"));
+        assert!(check_url("invalid-url").is_err());
+        assert!(check_url("https://").is_err());
+        assert!(check_url("ftp://").is_err());
     }
 
     #[test]
     fn test_urls_edge_cases() {
         // ข้อความที่คาดว่าจะยังไม่ผ่านการตรวจสอบ
-        assert!(!is_valid_url("http://:8080")); // ไม่มี host
-        assert!(!is_valid_url("://example.com")); // ไม่มี schema
+        assert!(check_url("http://:8080").is_err()); // ไม่มี host
+        assert!(check_url("://example.com").is_err()); // ไม่มี schema
     }
 }
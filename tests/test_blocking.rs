@@ -0,0 +1,100 @@
+#![cfg(feature = "blocking")]
+
+use wepay_rs::{
+    blocking::Wepay,
+    error::{StatusCode, WepayError},
+    idempotency::Submission,
+    money::Baht,
+    request::MobileTopupRequest,
+    types::{CallbackUrl, DestRef, MobileNumber},
+};
+
+fn wepay(prefix: &str) -> Wepay {
+    Wepay::builder()
+        .base_url(format!("{}/{}", mockito::server_url(), prefix))
+        .username("test_username")
+        .password("test_password")
+        .build()
+        .expect("Failed to build Wepay client.")
+}
+
+#[test]
+fn test_blocking_balance_mock() {
+    let mock_server = mockito::mock("POST", "/blocking-balance/client_api.json.php")
+        .match_body(mockito::Matcher::UrlEncoded(
+            "type".into(),
+            "balance_inquiry".into(),
+        ))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"code": "00000", "ledger_balance": "150.00", "available_balance": "120.50"}"#,
+        )
+        .create();
+
+    let result = wepay("blocking-balance")
+        .balance()
+        .expect("Failed to get user balance.");
+
+    assert_eq!(result.available_balance, Baht::from_satang(12050));
+    mock_server.assert();
+}
+
+#[test]
+fn test_blocking_topup_mobile_mock() {
+    let mock_server = mockito::mock("POST", "/blocking-topup/client_api.json.php")
+        .match_body(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("type".into(), "mtopup".into()),
+            mockito::Matcher::UrlEncoded("pay_to_ref1".into(), "0987654321".into()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"bill_id": 311057861, "code": "00000", "transaction_id": "394209458", "queue_id": 353183627, "total_amount": 10, "balance": "110.50"}"#,
+        )
+        .expect(2)
+        .create();
+
+    let wepay = wepay("blocking-topup");
+    let dest_ref = DestRef::try_from("TEST0000000000000099").unwrap();
+    let target = MobileNumber::try_from("0987654321").unwrap();
+    let callback = CallbackUrl::try_from("https://www.mywebsite.com/wepay_result.php").unwrap();
+
+    let result = wepay
+        .topup_mobile(&dest_ref, "TRMV", Baht::from_baht(10), &target, &callback)
+        .expect("Failed to topup mobile.");
+    assert_eq!(result.balance, Baht::from_satang(11050));
+
+    // ครั้งที่สองด้วย dest_ref เดิมจะได้ผลจากที่เก็บคำสั่งซื้อโดยไม่ส่งซ้ำ
+    let request = MobileTopupRequest::builder()
+        .dest_ref(dest_ref.clone())
+        .company("TRMV")
+        .amount(Baht::from_baht(10))
+        .target(target)
+        .callback(callback)
+        .build()
+        .unwrap();
+    let first = wepay
+        .submit_once(&request)
+        .expect("Failed to submit order.");
+    let second = wepay.clone().submit_once(&request).unwrap();
+    assert!(matches!(first, Submission::Placed(_)));
+    assert!(matches!(second, Submission::Placed(_)));
+
+    mock_server.assert();
+}
+
+#[test]
+fn test_blocking_error_mock() {
+    let mock_server = mockito::mock("GET", "/blocking-error/comp_export.php?json")
+        .with_status(500)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"code": "10001", "desc": "Internal error"}"#)
+        .create();
+
+    let error = wepay("blocking-error").product().unwrap_err();
+    assert!(matches!(error, WepayError::HttpError { status: 500, .. }));
+    assert_eq!(error.code(), Some(&StatusCode::InternalErrorDb));
+
+    mock_server.assert();
+}
@@ -0,0 +1,147 @@
+#![cfg(any(feature = "axum", feature = "actix"))]
+
+use std::sync::{Arc, Mutex};
+use wepay_rs::callback::{CallbackHandler, Notification};
+
+const BODY: &str =
+    "transaction_id=394209456&dest_ref=TEST0000000000000001&status=2&real_amount=5.00";
+
+type Received = Arc<Mutex<Vec<Notification>>>;
+
+/// สร้าง handler ที่เก็บ callback ที่ได้รับไว้ตรวจสอบภายหลัง
+fn recorder() -> (Received, impl CallbackHandler) {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let sink = received.clone();
+    let handler = move |n: Notification| {
+        sink.lock().unwrap().push(n);
+        std::future::ready(Ok(()))
+    };
+    (received, handler)
+}
+
+#[cfg(feature = "axum")]
+mod axum {
+    use super::*;
+    use ::axum::body::{Body, to_bytes};
+    use ::axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+    use wepay_rs::callback::HandlerError;
+    use wepay_rs::callback::axum::router;
+
+    /// handler ที่ล้มเหลวเสมอและเก็บ error ที่ได้รับทาง `on_error`
+    struct Failing(Arc<Mutex<Vec<String>>>);
+
+    #[async_trait::async_trait]
+    impl CallbackHandler for Failing {
+        async fn handle(&self, _: Notification) -> Result<(), HandlerError> {
+            Err("connection to db-01 refused".into())
+        }
+
+        fn on_error(&self, transaction_id: &str, error: &HandlerError) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{transaction_id}: {error}"));
+        }
+    }
+
+    fn request(body: &'static str) -> Request<Body> {
+        Request::post("/wepay_result")
+            .header("content-type", "application/x-www-form-urlencoded")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_axum_callback() {
+        let (received, handler) = recorder();
+        let app = router("/wepay_result", handler);
+
+        let response = app.oneshot(request(BODY)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"SUCCEED|UPDATE_ID=394209456");
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].dest_ref.as_str(), "TEST0000000000000001");
+    }
+
+    #[tokio::test]
+    async fn test_axum_callback_rejects_malformed() {
+        let (received, handler) = recorder();
+        let app = router("/wepay_result", handler);
+
+        let response = app.oneshot(request("status=2")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(received.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_axum_callback_handler_error() {
+        let app = router("/wepay_result", |_: Notification| async {
+            Err::<(), HandlerError>("database unavailable".into())
+        });
+
+        let response = app.oneshot(request(BODY)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_axum_callback_hides_handler_error() {
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let app = router("/wepay_result", Failing(errors.clone()));
+
+        let response = app.oneshot(request(BODY)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        // รายละเอียดของ error ไม่ถูกส่งกลับไปให้ wepay แต่ส่งให้ on_error แทน
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"FAILED");
+        assert_eq!(
+            *errors.lock().unwrap(),
+            ["394209456: connection to db-01 refused"]
+        );
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix {
+    use super::*;
+    use actix_web::{App, http::StatusCode, test};
+    use wepay_rs::callback::actix::resource;
+
+    fn request(body: &'static str) -> test::TestRequest {
+        test::TestRequest::post()
+            .uri("/wepay_result")
+            .insert_header(("content-type", "application/x-www-form-urlencoded"))
+            .set_payload(body)
+    }
+
+    #[actix_rt::test]
+    async fn test_actix_callback() {
+        let (received, handler) = recorder();
+        let app = test::init_service(App::new().service(resource("/wepay_result", handler))).await;
+
+        let response = test::call_service(&app, request(BODY).to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = test::read_body(response).await;
+        assert_eq!(&body[..], b"SUCCEED|UPDATE_ID=394209456");
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].transaction_id, "394209456");
+    }
+
+    #[actix_rt::test]
+    async fn test_actix_callback_rejects_malformed() {
+        let (received, handler) = recorder();
+        let app = test::init_service(App::new().service(resource("/wepay_result", handler))).await;
+
+        let response = test::call_service(&app, request("status=2").to_request()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(received.lock().unwrap().is_empty());
+    }
+}
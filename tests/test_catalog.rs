@@ -0,0 +1,326 @@
+use std::sync::Arc;
+use std::time::Duration;
+use wepay_rs::{
+    Wepay,
+    catalog::{Catalog, Category},
+    error::Rule,
+    money::Baht,
+    request::{BillPaymentRequest, BillpayRefs},
+    response::Balance,
+    types::{CallbackUrl, DestRef, MobileNumber},
+};
+
+const PRODUCT: &str = r#"{"data": {"mtopup": [{"company_id": "TRMV", "company_name": "True Move", "fee": 0, "minimum_amount": 5, "maximum_amount": 1000, "refundable": false, "denomination": []}], "cashcard": [], "gtopup": [], "billpay": [{"company_id": 12, "company_name": "การไฟฟ้าส่วนภูมิภาค", "fee": 5, "minimum_amount": 1, "maximum_amount": 50000, "barcode_only": false, "refs": [{"key": "ref1", "name": "Customer No."}], "additional_info": ""}]}}"#;
+
+// แต่ละ test ใช้ path ของตัวเองเพื่อไม่ให้ mock ชนกันเมื่อรันพร้อมกัน
+fn wepay(path: &str) -> Wepay {
+    Wepay::builder()
+        .base_url(format!("{}/{}", mockito::server_url(), path))
+        .username("test_username")
+        .password("test_password")
+        .build()
+        .expect("Failed to build Wepay client.")
+}
+
+#[tokio::test]
+async fn test_catalog_cached_within_ttl() {
+    let mock_server = mockito::mock("GET", "/cached/comp_export.php?json")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(PRODUCT)
+        .expect(1)
+        .create();
+
+    let catalog = Catalog::new(wepay("cached"), Duration::from_secs(60));
+    let first = catalog.get().await.expect("Failed to get catalog.");
+    let second = catalog.clone().get().await.expect("Failed to get catalog.");
+
+    assert!(Arc::ptr_eq(&first, &second));
+    assert_eq!(first.data.mtopup[0].company_id, "TRMV");
+
+    // ดัชนีสร้างจากรายการเดียวกันโดยไม่ส่งคำขอเพิ่ม
+    let index = catalog.index().await.expect("Failed to get index.");
+    assert!(Arc::ptr_eq(index.product(), &first));
+    assert_eq!(
+        index.amount_range_for(Category::Mtopup, "trmv"),
+        Some(Baht::from_baht(5)..=Baht::from_baht(1000))
+    );
+
+    mock_server.assert();
+}
+
+#[tokio::test]
+async fn test_catalog_revalidates_with_etag() {
+    let full_mock = mockito::mock("GET", "/etag/comp_export.php?json")
+        .match_header("if-none-match", mockito::Matcher::Missing)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("etag", "\"v1\"")
+        .with_body(PRODUCT)
+        .expect(1)
+        .create();
+    let not_modified_mock = mockito::mock("GET", "/etag/comp_export.php?json")
+        .match_header("if-none-match", "\"v1\"")
+        .with_status(304)
+        .expect(1)
+        .create();
+
+    // TTL เป็นศูนย์เพื่อให้ทุกครั้งต้องตรวจสอบกับ server
+    let catalog = Catalog::new(wepay("etag"), Duration::ZERO);
+    let first = catalog.get().await.expect("Failed to get catalog.");
+    let second = catalog.get().await.expect("Failed to revalidate catalog.");
+
+    assert!(Arc::ptr_eq(&first, &second));
+
+    full_mock.assert();
+    not_modified_mock.assert();
+}
+
+#[tokio::test]
+async fn test_catalog_falls_back_to_last_good_copy() {
+    let _full_mock = mockito::mock("GET", "/fallback/comp_export.php?json")
+        .match_header("if-none-match", mockito::Matcher::Missing)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("etag", "\"v1\"")
+        .with_body(PRODUCT)
+        .create();
+    let _error_mock = mockito::mock("GET", "/fallback/comp_export.php?json")
+        .match_header("if-none-match", "\"v1\"")
+        .with_status(500)
+        .with_header("content-type", "text/html")
+        .with_body("<html><body>Internal Server Error</body></html>")
+        .create();
+
+    let catalog = Catalog::new(wepay("fallback"), Duration::ZERO);
+    let first = catalog.get().await.expect("Failed to get catalog.");
+
+    // refresh ล้มเหลว แต่ get() ยังคืนรายการเดิม
+    assert!(catalog.refresh().await.is_err());
+    let second = catalog.get().await.expect("Failed to fall back to cache.");
+    assert!(Arc::ptr_eq(&first, &second));
+}
+
+#[tokio::test]
+async fn test_catalog_backs_off_after_failure() {
+    let _full_mock = mockito::mock("GET", "/backoff/comp_export.php?json")
+        .match_header("if-none-match", mockito::Matcher::Missing)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("etag", "\"v1\"")
+        .with_body(PRODUCT)
+        .create();
+    let error_mock = mockito::mock("GET", "/backoff/comp_export.php?json")
+        .match_header("if-none-match", "\"v1\"")
+        .with_status(503)
+        .with_header("content-type", "text/html")
+        .with_body("<html><body>Service Unavailable</body></html>")
+        .expect(1)
+        .create();
+
+    let catalog = Catalog::new(wepay("backoff"), Duration::ZERO);
+    let first = catalog.get().await.expect("Failed to get catalog.");
+
+    // cache หมดอายุและ wepay ล่ม ผู้เรียกทุกรายได้รายการเดิมจากการดึงที่ล้มเหลวเพียงครั้งเดียว
+    let mut calls = tokio::task::JoinSet::new();
+    for _ in 0..5 {
+        let catalog = catalog.clone();
+        calls.spawn(async move { catalog.get().await });
+    }
+    while let Some(result) = calls.join_next().await {
+        let product = result.unwrap().expect("Failed to fall back to cache.");
+        assert!(Arc::ptr_eq(&first, &product));
+    }
+    let index = catalog
+        .index()
+        .await
+        .expect("Failed to fall back to cache.");
+    assert!(Arc::ptr_eq(&first, index.product()));
+
+    error_mock.assert();
+}
+
+#[tokio::test]
+async fn test_catalog_retries_after_configured_backoff() {
+    let _full_mock = mockito::mock("GET", "/retry/comp_export.php?json")
+        .match_header("if-none-match", mockito::Matcher::Missing)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("etag", "\"v1\"")
+        .with_body(PRODUCT)
+        .create();
+    let error_mock = mockito::mock("GET", "/retry/comp_export.php?json")
+        .match_header("if-none-match", "\"v1\"")
+        .with_status(503)
+        .with_header("content-type", "text/html")
+        .with_body("<html><body>Service Unavailable</body></html>")
+        .expect(2)
+        .create();
+
+    let catalog = Catalog::builder(wepay("retry"))
+        .ttl(Duration::ZERO)
+        .failure_backoff(Duration::from_millis(100))
+        .build();
+    let first = catalog.get().await.expect("Failed to get catalog.");
+
+    // ล้มเหลวครั้งแรก แล้วใช้รายการเดิมโดยไม่ส่งคำขอจนกว่าจะพ้นช่วงพัก
+    for _ in 0..3 {
+        let product = catalog.get().await.expect("Failed to fall back to cache.");
+        assert!(Arc::ptr_eq(&first, &product));
+    }
+
+    // พ้นช่วงพักแล้วจึงลองดึงรายการใหม่อีกครั้ง
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    let product = catalog.get().await.expect("Failed to fall back to cache.");
+    assert!(Arc::ptr_eq(&first, &product));
+
+    error_mock.assert();
+}
+
+#[tokio::test]
+async fn test_catalog_without_cache_returns_error() {
+    let _error_mock = mockito::mock("GET", "/unavailable/comp_export.php?json")
+        .with_status(500)
+        .with_header("content-type", "text/html")
+        .with_body("<html><body>Internal Server Error</body></html>")
+        .create();
+
+    let catalog = Catalog::new(wepay("unavailable"), Duration::from_secs(60));
+    assert!(catalog.get().await.is_err());
+    assert!(catalog.cached().is_none());
+}
+
+#[tokio::test]
+async fn test_catalog_background_refresh() {
+    let _mock_server = mockito::mock("GET", "/background/comp_export.php?json")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(PRODUCT)
+        .create();
+
+    let catalog = Catalog::new(wepay("background"), Duration::from_millis(50));
+    let task = catalog.spawn_refresh();
+
+    for _ in 0..100 {
+        if catalog.cached().is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(catalog.cached().is_some());
+
+    // task หยุดเองเมื่อ Catalog ถูก drop
+    drop(catalog);
+    tokio::time::timeout(Duration::from_secs(5), task)
+        .await
+        .expect("Refresh task did not stop.")
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_preflight_rejects_amount_out_of_range() {
+    let _product_mock = mockito::mock("GET", "/preflight/comp_export.php?json")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(PRODUCT)
+        .create();
+    let order_mock = mockito::mock("POST", "/preflight/client_api.json.php")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"bill_id": 311057863, "code": "00000", "transaction_id": "394209456", "queue_id": 353183629, "total_amount": 50, "balance": "157.40"}"#,
+        )
+        .expect(1)
+        .create();
+
+    let catalog = Catalog::new(wepay("preflight"), Duration::from_secs(60));
+    let wepay = Wepay::builder()
+        .base_url(format!("{}/preflight", mockito::server_url()))
+        .username("test_username")
+        .password("test_password")
+        .preflight(catalog)
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let dest_ref: DestRef = "TEST01".parse().unwrap();
+    let target: MobileNumber = "0987654321".parse().unwrap();
+    let callback: CallbackUrl = "https://www.mywebsite.com/wepay_result.php"
+        .parse()
+        .unwrap();
+
+    // เกินช่วงที่บริษัทกำหนด จึงไม่ถูกส่งไปยัง wepay
+    let error = wepay
+        .topup_mobile(&dest_ref, "TRMV", Baht::from_baht(1001), &target, &callback)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error
+            .validation()
+            .expect("Expected validation error.")
+            .get("amount or pay_to_amount")
+            .unwrap()
+            .rule,
+        Rule::OutOfRange {
+            min: Baht::from_baht(5),
+            max: Baht::from_baht(1000),
+        }
+    );
+
+    // ไม่พบบริษัทในรายการสินค้า
+    let error = wepay
+        .topup_mobile(&dest_ref, "AIS", Baht::from_baht(50), &target, &callback)
+        .await
+        .unwrap_err();
+    assert!(
+        error
+            .validation()
+            .unwrap()
+            .get("company or pay_to_company")
+            .is_some()
+    );
+
+    wepay
+        .topup_mobile(&dest_ref, "TRMV", Baht::from_baht(50), &target, &callback)
+        .await
+        .expect("Failed to top up.");
+
+    order_mock.assert();
+}
+
+#[tokio::test]
+async fn test_catalog_quote() {
+    let _mock_server = mockito::mock("GET", "/quote/comp_export.php?json")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(PRODUCT)
+        .create();
+
+    let catalog = Catalog::new(wepay("quote"), Duration::from_secs(60));
+    let index = catalog.index().await.expect("Failed to get index.");
+    let company = index.billpay("12").expect("Company not found.");
+
+    let request = BillPaymentRequest::builder()
+        .dest_ref("TEST01".parse::<DestRef>().unwrap())
+        .company(company)
+        .amount("4.84".parse().unwrap())
+        .refs(BillpayRefs::new().ref1("020012345678"))
+        .callback(
+            "https://www.mywebsite.com/wepay_result.php"
+                .parse::<CallbackUrl>()
+                .unwrap(),
+        )
+        .build()
+        .expect("Failed to build request.");
+
+    let quote = catalog.quote(&request).await.expect("Failed to quote.");
+    assert_eq!(quote.category, Category::Billpay);
+    assert_eq!(quote.company, "12");
+    assert_eq!(quote.fee, Baht::from_baht(5));
+    assert_eq!(quote.total, Baht::from_satang(984));
+
+    let balance: Balance = serde_json::from_str(
+        r#"{"code": "00000", "ledger_balance": "10.00", "available_balance": "9.00"}"#,
+    )
+    .unwrap();
+    assert_eq!(quote.shortfall(&balance), Some(Baht::from_satang(84)));
+}
@@ -29,5 +29,5 @@ async fn test_balance() {
     let result = wepay.balance().await.expect("Failed to get user balance.");
 
     assert_eq!(result.code, "00000");
-    assert!(result.available_balance >= 0.0);
+    assert!(!result.available_balance.is_negative());
 }
@@ -1,4 +1,38 @@
-use wepay_rs::Wepay;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use wepay_rs::{
+    Wepay,
+    balance::{BalanceAlert, BalanceGuard, BalanceWatcher},
+    error::{Rule, StatusCode, WepayError},
+    idempotency::{DestRefGenerator, MemoryOrderStore, OrderState, OrderStore, Submission},
+    money::Baht,
+    request::{BillpayRefs, GtopupRefs, MobileTopupRequest, TransactionQuery},
+    response::TransactionStatus,
+    retry::RetryPolicy,
+    transport::{Cassette, HttpResponse, MemoryTransport, Method},
+    types::{CallbackUrl, DestRef, MobileNumber},
+};
+
+fn dest_ref(value: &str) -> DestRef {
+    DestRef::try_from(value).expect("Invalid dest_ref.")
+}
+
+fn mobile(value: &str) -> MobileNumber {
+    MobileNumber::try_from(value).expect("Invalid mobile number.")
+}
+
+fn rule(error: WepayError, field: &str) -> Rule {
+    let errors = error.validation().expect("Expected validation error.");
+    errors
+        .get(field)
+        .expect("Expected field error.")
+        .rule
+        .clone()
+}
+
+fn callback() -> CallbackUrl {
+    CallbackUrl::try_from("https://www.mywebsite.com/wepay_result.php").expect("Invalid callback.")
+}
 
 #[tokio::test]
 async fn test_balance_mock() {
@@ -20,11 +54,37 @@ async fn test_balance_mock() {
     let result = wepay.balance().await.expect("Failed to get user balance.");
 
     assert_eq!(result.code, "00000");
-    assert_eq!(result.available_balance, 299987.74);
+    assert_eq!(result.available_balance, Baht::from_satang(29998774));
 
     mock_server.assert();
 }
 
+#[tokio::test]
+async fn test_timeout() {
+    // server ที่รับ connection แต่ไม่ตอบกลับ
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            connections.push(socket);
+        }
+    });
+
+    let wepay = Wepay::builder()
+        .base_url(base_url)
+        .username("test_username")
+        .password("test_password")
+        .timeout(Duration::from_millis(100))
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let start = Instant::now();
+    let error = wepay.balance().await.unwrap_err();
+    assert!(matches!(error, WepayError::RequestError(ref e) if e.is_timeout()));
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
 #[tokio::test]
 async fn test_topup_mobile_mock() {
     let mock_server = mockito::mock("POST", "/client_api.json.php")
@@ -44,17 +104,970 @@ async fn test_topup_mobile_mock() {
 
     let result = wepay
         .topup_mobile(
-            "TEST0000000000000001",
+            &dest_ref("TEST0000000000000001"),
             "TRMV",
-            &5f32, // 5 บาท
-            "0987654321",
-            "https://www.mywebsite.com/wepay_result.php",
+            Baht::from_baht(5), // 5 บาท
+            &mobile("0987654321"),
+            &callback(),
         )
         .await
         .expect("Failed to topup mobile.");
 
     assert_eq!(result.code, "00000");
-    assert_eq!(result.balance, 162.24);
+    assert_eq!(result.balance, Baht::from_satang(16224));
+
+    mock_server.assert();
+}
+
+fn billpay_company() -> wepay_rs::response::Billpay {
+    serde_json::from_str(
+        r#"{"company_id": "PEA", "company_name": "การไฟฟ้าส่วนภูมิภาค", "fee": 0, "minimum_amount": 1.0, "maximum_amount": 50000, "barcode_only": false, "refs": [{"key": "ref1", "name": "Customer No."}, {"key": "ref2", "name": "Invoice No."}], "additional_info": ""}"#,
+    )
+    .expect("Failed to parse billpay company.")
+}
+
+#[tokio::test]
+async fn test_billpay_mock() {
+    let mock_server = mockito::mock("POST", "/client_api.json.php")
+        .match_body(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("type".into(), "billpay".into()),
+            mockito::Matcher::UrlEncoded("pay_to_company".into(), "PEA".into()),
+            mockito::Matcher::UrlEncoded("pay_to_ref1".into(), "020012345678".into()),
+            mockito::Matcher::UrlEncoded("pay_to_ref2".into(), "112233".into()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"bill_id": 311057860, "code": "00000", "transaction_id": "394209457", "queue_id": 353183626, "total_amount": 523.5, "balance": "1000.00"}"#,
+        )
+        .create();
+
+    let wepay = Wepay::builder()
+        .base_url(mockito::server_url())
+        .username("test_username")
+        .password("test_password")
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let refs = BillpayRefs::new().ref1("020012345678").ref2("112233");
+    let result = wepay
+        .billpay(
+            &dest_ref("TEST0000000000000002"),
+            &billpay_company(),
+            Baht::from_satang(52350),
+            &refs,
+            &callback(),
+        )
+        .await
+        .expect("Failed to pay bill.");
+
+    assert_eq!(result.code, "00000");
+    assert_eq!(result.total_amount, Baht::from_satang(52350));
+
+    mock_server.assert();
+}
+
+#[tokio::test]
+async fn test_billpay_rejects_invalid_input() {
+    let wepay = Wepay::builder()
+        .username("test_username")
+        .password("test_password")
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let company = billpay_company();
+    let callback = callback();
+    let test01 = dest_ref("TEST01");
+
+    // จำนวนเงินเกินช่วงที่บริษัทกำหนด
+    let refs = BillpayRefs::new().ref1("020012345678").ref2("112233");
+    let result = wepay
+        .billpay(&test01, &company, Baht::from_baht(50001), &refs, &callback)
+        .await;
+    assert_eq!(
+        rule(result.unwrap_err(), "amount or pay_to_amount"),
+        Rule::OutOfRange {
+            min: Baht::from_baht(1),
+            max: Baht::from_baht(50000),
+        }
+    );
+
+    // ไม่ได้ระบุ Ref.2 ที่บริษัทกำหนด
+    let refs = BillpayRefs::new().ref1("020012345678");
+    let result = wepay
+        .billpay(&test01, &company, Baht::from_baht(100), &refs, &callback)
+        .await;
+    assert_eq!(
+        rule(result.unwrap_err(), "ref2 or pay_to_ref2"),
+        Rule::Missing
+    );
+}
+
+fn cashcard_company() -> wepay_rs::response::Cashcard {
+    serde_json::from_str(
+        r#"{"company_id": "TMNCASH", "company_name": "TrueMoney Cash Card", "fee": 0, "denomination": [{"price": 50.0, "description": null}, {"price": 90.0, "description": null}]}"#,
+    )
+    .expect("Failed to parse cashcard company.")
+}
+
+#[tokio::test]
+async fn test_cashcard_mock() {
+    let mock_server = mockito::mock("POST", "/client_api.json.php")
+        .match_body(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("type".into(), "cashcard".into()),
+            mockito::Matcher::UrlEncoded("pay_to_company".into(), "TMNCASH".into()),
+            mockito::Matcher::UrlEncoded("pay_to_amount".into(), "90.00".into()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"bill_id": 311057861, "code": "00000", "transaction_id": "394209458", "queue_id": 353183627, "total_amount": 88.2, "balance": "911.80"}"#,
+        )
+        .create();
+
+    let wepay = Wepay::builder()
+        .base_url(mockito::server_url())
+        .username("test_username")
+        .password("test_password")
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let result = wepay
+        .cashcard(
+            &dest_ref("TEST0000000000000003"),
+            &cashcard_company(),
+            Baht::from_baht(90),
+            &callback(),
+        )
+        .await
+        .expect("Failed to buy cash card.");
+
+    assert_eq!(result.code, "00000");
+    assert_eq!(result.balance, Baht::from_satang(91180));
+
+    mock_server.assert();
+}
+
+#[tokio::test]
+async fn test_cashcard_rejects_unknown_denomination() {
+    let wepay = Wepay::builder()
+        .username("test_username")
+        .password("test_password")
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let result = wepay
+        .cashcard(
+            &dest_ref("TEST01"),
+            &cashcard_company(),
+            Baht::from_baht(100),
+            &callback(),
+        )
+        .await;
+    assert!(matches!(
+        rule(result.unwrap_err(), "amount or pay_to_amount"),
+        Rule::NotDenomination { .. }
+    ));
+}
+
+fn gtopup_company() -> wepay_rs::response::Gtopup {
+    serde_json::from_str(
+        r#"{"company_id": "ROV", "company_name": "RoV", "fee": 0, "denomination": [{"price": 100.0, "description": "240 คูปอง"}], "congestion": [], "gameservers": [{"value": "TH", "name": "Thailand"}, {"value": "SEA", "name": "South East Asia"}], "refs_format": {"ref1": "[0-9]{6,12}", "ref2": null, "ref3": null}}"#,
+    )
+    .expect("Failed to parse gtopup company.")
+}
+
+#[tokio::test]
+async fn test_gtopup_mock() {
+    let mock_server = mockito::mock("POST", "/client_api.json.php")
+        .match_body(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("type".into(), "gtopup".into()),
+            mockito::Matcher::UrlEncoded("pay_to_company".into(), "ROV".into()),
+            mockito::Matcher::UrlEncoded("pay_to_ref1".into(), "123456789".into()),
+            mockito::Matcher::UrlEncoded("pay_to_server".into(), "TH".into()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"bill_id": 311057862, "code": "00000", "transaction_id": "394209459", "queue_id": 353183628, "total_amount": 97.0, "balance": "814.80"}"#,
+        )
+        .create();
+
+    let wepay = Wepay::builder()
+        .base_url(mockito::server_url())
+        .username("test_username")
+        .password("test_password")
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let refs = GtopupRefs::new().ref1("123456789");
+    let result = wepay
+        .gtopup(
+            &dest_ref("TEST0000000000000004"),
+            &gtopup_company(),
+            Baht::from_baht(100),
+            &refs,
+            Some("TH"),
+            &callback(),
+        )
+        .await
+        .expect("Failed to topup game.");
+
+    assert_eq!(result.code, "00000");
+    assert_eq!(result.total_amount, Baht::from_baht(97));
+
+    mock_server.assert();
+}
+
+#[tokio::test]
+async fn test_gtopup_rejects_invalid_input() {
+    let wepay = Wepay::builder()
+        .username("test_username")
+        .password("test_password")
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let company = gtopup_company();
+    let callback = callback();
+    let test01 = dest_ref("TEST01");
+
+    // Ref.1 ไม่ตรงตามรูปแบบ
+    let refs = GtopupRefs::new().ref1("player-one");
+    let result = wepay
+        .gtopup(
+            &test01,
+            &company,
+            Baht::from_baht(100),
+            &refs,
+            Some("TH"),
+            &callback,
+        )
+        .await;
+    assert!(matches!(
+        rule(result.unwrap_err(), "ref1 or pay_to_ref1"),
+        Rule::PatternMismatch { .. }
+    ));
+
+    // เซิร์ฟเวอร์ไม่อยู่ในรายการ
+    let refs = GtopupRefs::new().ref1("123456789");
+    let result = wepay
+        .gtopup(
+            &test01,
+            &company,
+            Baht::from_baht(100),
+            &refs,
+            Some("EU"),
+            &callback,
+        )
+        .await;
+    assert!(matches!(
+        rule(result.unwrap_err(), "server or pay_to_server"),
+        Rule::NotAllowed { .. }
+    ));
+}
+
+#[tokio::test]
+async fn test_transaction_status_mock() {
+    let mock_server = mockito::mock("POST", "/client_api.json.php")
+        .match_body(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("type".into(), "transaction_inquiry".into()),
+            mockito::Matcher::UrlEncoded("dest_ref".into(), "TEST0000000000000001".into()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"code": "00000", "transaction_id": "394209456", "dest_ref": "TEST0000000000000001", "status": "2", "operator_trxnsid": "AB1234", "sms": null}"#,
+        )
+        .create();
+
+    let wepay = Wepay::builder()
+        .base_url(mockito::server_url())
+        .username("test_username")
+        .password("test_password")
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let result = wepay
+        .transaction_status(&TransactionQuery::DestRef(dest_ref("TEST0000000000000001")))
+        .await
+        .expect("Failed to get transaction status.");
+
+    assert_eq!(result.transaction_id, "394209456");
+    assert_eq!(result.status, TransactionStatus::Success);
+
+    mock_server.assert();
+}
+
+#[tokio::test]
+async fn test_transaction_status_accepts_server_dest_ref() {
+    // dest_ref จาก wepay ที่ไม่ตรงกับกฎของ `DestRef` ไม่ทำให้การตรวจสอบสถานะล้มเหลว
+    let mock_server = mockito::mock("POST", "/lenient/client_api.json.php")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"code": "00000", "transaction_id": "394209457", "dest_ref": "legacy_ref-0001", "status": "2", "operator_trxnsid": null, "sms": null}"#,
+        )
+        .create();
+
+    let wepay = Wepay::builder()
+        .base_url(format!("{}/lenient", mockito::server_url()))
+        .username("test_username")
+        .password("test_password")
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let result = wepay
+        .transaction_status(&TransactionQuery::TransactionId("394209457".into()))
+        .await
+        .expect("Failed to get transaction status.");
+
+    assert_eq!(result.dest_ref, "legacy_ref-0001");
+    assert_eq!(result.status, TransactionStatus::Success);
+
+    mock_server.assert();
+}
+
+#[tokio::test]
+async fn test_transaction_status_not_found_mock() {
+    let mock_server = mockito::mock("POST", "/client_api.json.php")
+        .match_body(mockito::Matcher::UrlEncoded(
+            "transaction_id".into(),
+            "394209999".into(),
+        ))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"code": "30003", "desc": "Transaction ID not found"}"#)
+        .create();
+
+    let wepay = Wepay::builder()
+        .base_url(mockito::server_url())
+        .username("test_username")
+        .password("test_password")
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let result = wepay
+        .transaction_status(&TransactionQuery::TransactionId("394209999".into()))
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(WepayError::ApiError {
+            code: StatusCode::TransactionNotFound,
+            ..
+        })
+    ));
+
+    mock_server.assert();
+}
+
+fn retry_policy() -> RetryPolicy {
+    RetryPolicy::new()
+        .max_attempts(3)
+        .initial_backoff(Duration::from_millis(1))
+        .jitter(false)
+}
+
+#[tokio::test]
+async fn test_balance_retry_mock() {
+    let mock_server = mockito::mock("POST", "/client_api.json.php")
+        .match_body(mockito::Matcher::UrlEncoded(
+            "type".into(),
+            "balance_inquiry".into(),
+        ))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"code": "10003", "desc": "Too Many Requests"}"#)
+        .expect(3)
+        .create();
+
+    let wepay = Wepay::builder()
+        .base_url(mockito::server_url())
+        .username("test_username")
+        .password("test_password")
+        .retry(retry_policy())
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let result = wepay.balance().await;
+
+    assert!(matches!(
+        result,
+        Err(WepayError::ApiError {
+            code: StatusCode::InternalErrorTooManyRequests,
+            ..
+        })
+    ));
+
+    // ส่งคำขอครบตามจำนวนครั้งที่กำหนด
+    mock_server.assert();
+}
+
+#[tokio::test]
+async fn test_topup_mobile_not_retried_mock() {
+    let mock_server = mockito::mock("POST", "/client_api.json.php")
+        .match_body(mockito::Matcher::UrlEncoded("type".into(), "mtopup".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"code": "10001", "desc": "Internal Error"}"#)
+        .expect(1)
+        .create();
+
+    let wepay = Wepay::builder()
+        .base_url(mockito::server_url())
+        .username("test_username")
+        .password("test_password")
+        .retry(retry_policy())
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let result = wepay
+        .topup_mobile(
+            &dest_ref("TEST0000000000000005"),
+            "TRMV",
+            Baht::from_baht(5),
+            &mobile("0987654321"),
+            &callback(),
+        )
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(WepayError::ApiError {
+            code: StatusCode::InternalErrorDb,
+            ..
+        })
+    ));
+
+    // wepay ได้รับคำสั่งซื้อแล้วจึงต้องไม่ส่งซ้ำ
+    mock_server.assert();
+}
+
+#[tokio::test]
+async fn test_rate_limit_shared_between_clones_mock() {
+    let mock_server = mockito::mock("POST", "/client_api.json.php")
+        .match_body(mockito::Matcher::UrlEncoded(
+            "type".into(),
+            "balance_inquiry".into(),
+        ))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"code": "00000", "ledger_balance": "299987.74", "available_balance": "299987.74"}"#,
+        )
+        .expect(3)
+        .create();
+
+    let wepay = Wepay::builder()
+        .base_url(mockito::server_url())
+        .username("test_username")
+        .password("test_password")
+        .rate_limit(20.0, 1)
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let (a, b) = (wepay.clone(), wepay.clone());
+    let start = Instant::now();
+    let (r1, r2, r3) = tokio::join!(wepay.balance(), a.balance(), b.balance());
+
+    assert!(r1.is_ok() && r2.is_ok() && r3.is_ok());
+    // คำขอแรกส่งทันที อีกสองคำขอต้องรอคิวคำขอละ 50ms
+    assert!(start.elapsed() >= Duration::from_millis(95));
+
+    mock_server.assert();
+}
+
+fn topup_request(dest_ref: DestRef) -> MobileTopupRequest {
+    MobileTopupRequest::builder()
+        .dest_ref(dest_ref)
+        .company("TRMV")
+        .amount(Baht::from_baht(5))
+        .target(mobile("0987654321"))
+        .callback(callback())
+        .build()
+        .expect("Failed to build request.")
+}
 
+#[tokio::test]
+async fn test_submit_once_returns_previous_order_mock() {
+    let mock_server = mockito::mock("POST", "/client_api.json.php")
+        .match_body(mockito::Matcher::UrlEncoded("type".into(), "mtopup".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"bill_id": 311057863, "code": "00000", "transaction_id": "394209460", "queue_id": 353183629, "total_amount": 4.84, "balance": "157.40"}"#,
+        )
+        .expect(1)
+        .create();
+
+    let wepay = Wepay::builder()
+        .base_url(mockito::server_url())
+        .username("test_username")
+        .password("test_password")
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let dest_ref = DestRefGenerator::new("TEST")
+        .expect("Failed to create generator.")
+        .generate();
+    let request = topup_request(dest_ref);
+
+    let first = wepay.submit_once(&request).await.unwrap();
+    let second = wepay.submit_once(&request).await.unwrap();
+
+    assert_eq!(first.transaction_id(), "394209460");
+    assert_eq!(second.transaction_id(), "394209460");
+
+    // คำสั่งซื้อถูกส่งไปยัง wepay เพียงครั้งเดียว
     mock_server.assert();
 }
+
+#[tokio::test]
+async fn test_submit_once_concurrent_same_dest_ref_mock() {
+    let order_mock = mockito::mock("POST", "/concurrent/client_api.json.php")
+        .match_body(mockito::Matcher::UrlEncoded("type".into(), "mtopup".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"bill_id": 311057865, "code": "00000", "transaction_id": "394209462", "queue_id": 353183631, "total_amount": 4.84, "balance": "147.72"}"#,
+        )
+        .expect(1)
+        .create();
+    let inquiry_mock = mockito::mock("POST", "/concurrent/client_api.json.php")
+        .match_body(mockito::Matcher::UrlEncoded(
+            "type".into(),
+            "transaction_inquiry".into(),
+        ))
+        .expect(0)
+        .create();
+
+    let wepay = Wepay::builder()
+        .base_url(format!("{}/concurrent", mockito::server_url()))
+        .username("test_username")
+        .password("test_password")
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    // ครั้งที่สองรอครั้งแรกเสร็จแล้วได้ผลเดิม โดยไม่ส่งคำสั่งซื้อหรือตรวจสอบสถานะเพิ่ม
+    let request = topup_request(dest_ref("TEST0000000000000007"));
+    let other = wepay.clone();
+    let (first, second) = tokio::join!(wepay.submit_once(&request), other.submit_once(&request));
+
+    assert_eq!(first.unwrap().transaction_id(), "394209462");
+    assert_eq!(second.unwrap().transaction_id(), "394209462");
+
+    order_mock.assert();
+    inquiry_mock.assert();
+}
+
+#[tokio::test]
+async fn test_submit_once_keeps_previous_pending_mock() {
+    let inquiry_mock = mockito::mock("POST", "/pending/client_api.json.php")
+        .match_body(mockito::Matcher::UrlEncoded(
+            "type".into(),
+            "transaction_inquiry".into(),
+        ))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"code": "30003", "desc": "Transaction ID not found"}"#)
+        .create();
+
+    // ครั้งก่อนส่งไปแล้วแต่ไม่ทราบผล
+    let store = Arc::new(MemoryOrderStore::new());
+    store
+        .save("TEST0000000000000008", &OrderState::Pending)
+        .await
+        .unwrap();
+
+    let guard = BalanceGuard::new();
+    guard.update(Baht::from_baht(1));
+
+    let wepay = Wepay::builder()
+        .base_url(format!("{}/pending", mockito::server_url()))
+        .username("test_username")
+        .password("test_password")
+        .order_store(store.clone())
+        .balance_guard(guard)
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let result = wepay
+        .submit_once(&topup_request(dest_ref("TEST0000000000000008")))
+        .await;
+    assert!(matches!(
+        result,
+        Err(WepayError::InsufficientBalance { .. })
+    ));
+
+    // ข้อผิดพลาดในเครื่องต้องไม่ลบ Pending ของครั้งก่อน
+    assert!(matches!(
+        store.load("TEST0000000000000008").await.unwrap(),
+        Some(OrderState::Pending)
+    ));
+
+    inquiry_mock.assert();
+}
+
+#[tokio::test]
+async fn test_submit_once_resolves_duplicate_mock() {
+    let order_mock = mockito::mock("POST", "/client_api.json.php")
+        .match_body(mockito::Matcher::UrlEncoded("type".into(), "mtopup".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"code": "30016", "desc": "Duplicate dest_ref"}"#)
+        .create();
+    let inquiry_mock = mockito::mock("POST", "/client_api.json.php")
+        .match_body(mockito::Matcher::UrlEncoded(
+            "type".into(),
+            "transaction_inquiry".into(),
+        ))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"code": "00000", "transaction_id": "394209456", "dest_ref": "TEST0000000000000001", "status": "2", "operator_trxnsid": null, "sms": null}"#,
+        )
+        .create();
+
+    let wepay = Wepay::builder()
+        .base_url(mockito::server_url())
+        .username("test_username")
+        .password("test_password")
+        .order_store(MemoryOrderStore::new())
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let result = wepay
+        .submit_once(&topup_request(dest_ref("TEST0000000000000001")))
+        .await
+        .expect("Failed to resolve duplicate order.");
+
+    assert!(matches!(result, Submission::Resolved(_)));
+    assert_eq!(result.transaction_id(), "394209456");
+
+    order_mock.assert();
+    inquiry_mock.assert();
+}
+
+#[tokio::test]
+async fn test_submit_mock() {
+    let mock_server = mockito::mock("POST", "/client_api.json.php")
+        .match_body(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("type".into(), "mtopup".into()),
+            mockito::Matcher::UrlEncoded("dest_ref".into(), "TEST0000000000000006".into()),
+            mockito::Matcher::UrlEncoded("pay_to_ref1".into(), "0987654321".into()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"bill_id": 311057864, "code": "00000", "transaction_id": "394209461", "queue_id": 353183630, "total_amount": 4.84, "balance": "152.56"}"#,
+        )
+        .create();
+
+    let wepay = Wepay::builder()
+        .base_url(mockito::server_url())
+        .username("test_username")
+        .password("test_password")
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let request = MobileTopupRequest::builder()
+        .dest_ref(dest_ref("TEST0000000000000006"))
+        .company("TRMV")
+        .amount(Baht::from_baht(5))
+        .target(mobile("0987654321"))
+        .callback(callback())
+        .build()
+        .expect("Failed to build request.");
+
+    let result = wepay.submit(&request).await.expect("Failed to submit.");
+
+    assert_eq!(result.transaction_id, "394209461");
+    assert_eq!(result.total_amount, Baht::from_satang(484));
+
+    mock_server.assert();
+}
+
+#[tokio::test]
+async fn test_balance_guard_mock() {
+    let balance_mock = mockito::mock("POST", "/guard/client_api.json.php")
+        .match_body(mockito::Matcher::UrlEncoded(
+            "type".into(),
+            "balance_inquiry".into(),
+        ))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"code": "00000", "ledger_balance": "12.00", "available_balance": "10.00"}"#)
+        .expect(1)
+        .create();
+    let order_mock = mockito::mock("POST", "/guard/client_api.json.php")
+        .match_body(mockito::Matcher::UrlEncoded("type".into(), "mtopup".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"bill_id": 311057864, "code": "00000", "transaction_id": "394209461", "queue_id": 353183630, "total_amount": 5, "balance": "5.00"}"#,
+        )
+        .expect(1)
+        .create();
+
+    let low_balance = Arc::new(Mutex::new(None));
+    let alert = low_balance.clone();
+    let guard = BalanceGuard::new().on_low_balance(Baht::from_baht(8), move |balance| {
+        *alert.lock().unwrap() = Some(balance);
+    });
+
+    let wepay = Wepay::builder()
+        .base_url(format!("{}/guard", mockito::server_url()))
+        .username("test_username")
+        .password("test_password")
+        .balance_guard(guard.clone())
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    // ดึงยอดเงินก่อนคำสั่งซื้อแรก แล้วส่งคำสั่งซื้อที่ยอดเงินพอ
+    wepay
+        .topup_mobile(
+            &dest_ref("TEST01"),
+            "TRMV",
+            Baht::from_baht(5),
+            &mobile("0987654321"),
+            &callback(),
+        )
+        .await
+        .expect("Failed to top up.");
+    assert_eq!(guard.available(), Some(Baht::from_baht(5)));
+    assert_eq!(guard.reserved(), Baht::ZERO);
+    assert_eq!(*low_balance.lock().unwrap(), Some(Baht::from_baht(5)));
+
+    // ยอดเงินคงเหลือไม่พอ คำสั่งซื้อถูกปฏิเสธโดยไม่ส่งไปยัง wepay
+    let result = wepay
+        .topup_mobile(
+            &dest_ref("TEST02"),
+            "TRMV",
+            Baht::from_baht(10),
+            &mobile("0987654321"),
+            &callback(),
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(WepayError::InsufficientBalance { available, .. }) if available == Baht::from_baht(5)
+    ));
+
+    balance_mock.assert();
+    order_mock.assert();
+}
+
+#[tokio::test]
+async fn test_balance_watcher_mock() {
+    let balance_mock = mockito::mock("POST", "/watch/client_api.json.php")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"code": "00000", "ledger_balance": "120.00", "available_balance": "90.00"}"#)
+        .expect_at_least(2)
+        .create();
+
+    let alerts = Arc::new(Mutex::new(Vec::new()));
+    let received = alerts.clone();
+    let watch = BalanceWatcher::new(
+        Wepay::builder()
+            .base_url(format!("{}/watch", mockito::server_url()))
+            .username("test_username")
+            .password("test_password")
+            .build()
+            .expect("Failed to build Wepay client."),
+    )
+    .interval(Duration::from_millis(20))
+    .threshold(Baht::from_baht(100))
+    .on_alert(move |alert| received.lock().unwrap().push(alert.clone()))
+    .spawn();
+
+    let mut receiver = watch.subscribe();
+    tokio::time::timeout(Duration::from_secs(5), receiver.wait_for(Option::is_some))
+        .await
+        .expect("Balance was not checked.")
+        .unwrap();
+
+    let snapshot = watch.latest().unwrap();
+    assert_eq!(snapshot.ledger_balance, Baht::from_baht(120));
+    assert_eq!(snapshot.available_balance, Baht::from_baht(90));
+
+    // ตรวจสอบหลายรอบแล้ว แต่แจ้งเตือนเพียงครั้งเดียวเพราะยอดเงินยังต่ำกว่าเกณฑ์
+    receiver.mark_unchanged();
+    receiver.changed().await.unwrap();
+    assert_eq!(
+        *alerts.lock().unwrap(),
+        [BalanceAlert::Below {
+            threshold: Baht::from_baht(100),
+            balance: Baht::from_baht(90),
+        }]
+    );
+
+    balance_mock.assert();
+}
+
+#[tokio::test]
+async fn test_product_error_mock() {
+    let _html_mock = mockito::mock("GET", "/product_html/comp_export.php?json")
+        .with_status(502)
+        .with_header("content-type", "text/html")
+        .with_body("<html><body>Bad Gateway</body></html>")
+        .create();
+    let _error_mock = mockito::mock("GET", "/product_error/comp_export.php?json")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"code": "20005", "desc": "IP address not allowed"}"#)
+        .create();
+
+    let wepay = |path: &str| {
+        Wepay::builder()
+            .base_url(format!("{}/{}", mockito::server_url(), path))
+            .retry(RetryPolicy::new().max_attempts(1))
+            .build()
+            .expect("Failed to build Wepay client.")
+    };
+
+    // หน้า HTML ถูกแปลงเป็น HttpError พร้อม status และตัวอย่าง body
+    match wepay("product_html").product().await {
+        Err(WepayError::HttpError { status, code, body }) => {
+            assert_eq!(status, 502);
+            assert_eq!(code, None);
+            assert!(body.contains("Bad Gateway"));
+        }
+        other => panic!("Expected HttpError, got {:?}", other),
+    }
+
+    // error ของ wepay ใน body ถูกแปลงเป็น ApiError เช่นเดียวกับคำขออื่น
+    let error = wepay("product_error").product().await.unwrap_err();
+    assert_eq!(error.code(), Some(&StatusCode::UnauthorizedIp));
+}
+
+#[tokio::test]
+async fn test_http_error_with_wepay_code_mock() {
+    let mock_server = mockito::mock("POST", "/http_error/client_api.json.php")
+        .with_status(429)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"code": "10003", "desc": "Too Many Requests"}"#)
+        .expect(2)
+        .create();
+
+    let wepay = Wepay::builder()
+        .base_url(format!("{}/http_error", mockito::server_url()))
+        .username("test_username")
+        .password("test_password")
+        .retry(
+            RetryPolicy::new()
+                .max_attempts(2)
+                .initial_backoff(Duration::from_millis(1)),
+        )
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    // รหัส 10003 ส่งซ้ำได้ตามนโยบาย แม้จะมาพร้อม HTTP 429
+    let error = wepay.balance().await.unwrap_err();
+    assert_eq!(error.status(), Some(429));
+    assert_eq!(
+        error.code(),
+        Some(&StatusCode::InternalErrorTooManyRequests)
+    );
+
+    mock_server.assert();
+}
+
+#[tokio::test]
+async fn test_record_and_replay_mock() {
+    let balance_mock = mockito::mock("POST", "/cassette/client_api.json.php")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"code": "00000", "ledger_balance": "120.00", "available_balance": "90.00"}"#)
+        .expect(1)
+        .create();
+    let product_mock = mockito::mock("GET", "/cassette/comp_export.php?json")
+        .with_status(502)
+        .with_header("content-type", "text/html")
+        .with_body("<html><body>Bad Gateway</body></html>")
+        .expect(1)
+        .create();
+
+    let path = std::env::temp_dir().join(format!("wepay-cassette-{}.json", std::process::id()));
+    let recorder = Wepay::builder()
+        .base_url(format!("{}/cassette", mockito::server_url()))
+        .username("test_username")
+        .password("test_password")
+        .record(&path)
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    recorder.balance().await.expect("Failed to get balance.");
+    assert!(recorder.product().await.is_err());
+    balance_mock.assert();
+    product_mock.assert();
+    recorder
+        .flush_recording()
+        .await
+        .expect("Failed to write cassette.");
+
+    // ไฟล์ไม่มีข้อมูลรับรอง
+    let json = std::fs::read_to_string(&path).unwrap();
+    assert!(!json.contains("test_password"));
+    let cassette = Cassette::load(&path).expect("Failed to load cassette.");
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(cassette.interactions.len(), 2);
+
+    // เล่นซ้ำโดยไม่เชื่อมต่อ server และได้ผลลัพธ์เดิม
+    let replayer = Wepay::builder()
+        .base_url("http://127.0.0.1:9")
+        .username("other_username")
+        .password("other_password")
+        .replay(cassette)
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let balance = replayer.balance().await.expect("Failed to replay balance.");
+    assert_eq!(balance.available_balance, Baht::from_baht(90));
+    assert!(matches!(
+        replayer.product().await,
+        Err(WepayError::HttpError { status: 502, .. })
+    ));
+
+    // คำขอที่ไม่ได้บันทึกไว้
+    assert!(matches!(
+        replayer.balance().await,
+        Err(WepayError::Transport(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_memory_transport() {
+    let transport = MemoryTransport::new();
+    transport.push(HttpResponse::new(
+        200,
+        r#"{"code": "00000", "ledger_balance": "50.00", "available_balance": "40.00"}"#,
+    ));
+    transport.push(HttpResponse::new(503, "Service Unavailable"));
+
+    let wepay = Wepay::builder()
+        .username("test_username")
+        .password("test_password")
+        .transport(transport.clone())
+        .build()
+        .expect("Failed to build Wepay client.");
+
+    let balance = wepay.balance().await.expect("Failed to get balance.");
+    assert_eq!(balance.available_balance, Baht::from_baht(40));
+    assert!(matches!(
+        wepay.product().await,
+        Err(WepayError::HttpError { status: 503, .. })
+    ));
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].method, Method::Post);
+    assert_eq!(
+        requests[0].url,
+        "https://www.wepay.in.th/client_api.json.php"
+    );
+    assert_eq!(requests[0].field("username"), Some("test_username"));
+    assert_eq!(requests[0].field("type"), Some("balance_inquiry"));
+    assert_eq!(requests[1].method, Method::Get);
+    assert_eq!(transport.remaining(), 0);
+}
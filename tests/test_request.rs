@@ -0,0 +1,193 @@
+use wepay_rs::{
+    error::{Rule, WepayError},
+    money::Baht,
+    request::{MobileTopupRequest, OrderRequest},
+    types::{CallbackUrl, DestRef, MobileNumber},
+};
+
+fn callback() -> CallbackUrl {
+    CallbackUrl::try_from("https://www.mywebsite.com/wepay_result.php").expect("Invalid callback.")
+}
+
+#[test]
+fn test_mobile_topup_request_build() {
+    let request = MobileTopupRequest::builder()
+        .dest_ref("TEST0000000000000001".parse::<DestRef>().unwrap())
+        .company("TRMV")
+        .amount(Baht::from_baht(5))
+        .target("081-234-5678".parse::<MobileNumber>().unwrap())
+        .callback(callback())
+        .build()
+        .expect("Failed to build request.");
+
+    assert_eq!(request.order_type(), "mtopup");
+    assert_eq!(request.dest_ref().as_str(), "TEST0000000000000001");
+
+    // เบอร์มือถือถูกส่งในรูปแบบตัวเลขล้วน
+    let params = request.params();
+    assert!(params.contains(&("pay_to_amount", "5.00".to_string())));
+    assert!(params.contains(&("pay_to_ref1", "0812345678".to_string())));
+}
+
+#[test]
+fn test_mobile_topup_request_missing_field() {
+    let result = MobileTopupRequest::builder()
+        .dest_ref("TEST01".parse::<DestRef>().unwrap())
+        .amount(Baht::from_baht(5))
+        .target("0987654321".parse::<MobileNumber>().unwrap())
+        .callback(callback())
+        .build();
+
+    let error = result.unwrap_err();
+    let errors = error.validation().expect("Expected validation error.");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors.get("company").unwrap().rule, Rule::Missing);
+}
+
+#[test]
+fn test_mobile_topup_request_collects_all_failures() {
+    let result = MobileTopupRequest::builder()
+        .amount(Baht::from_baht(5))
+        .build();
+
+    let error = result.unwrap_err();
+    let fields: Vec<_> = error
+        .validation()
+        .unwrap()
+        .iter()
+        .map(|e| e.field)
+        .collect();
+    assert_eq!(fields, ["dest_ref", "company", "target", "callback"]);
+}
+
+#[test]
+fn test_mobile_topup_request_collects_invalid_values() {
+    let result = MobileTopupRequest::builder()
+        .dest_ref("under_score")
+        .company("TRMV")
+        .amount(Baht::from_baht(5))
+        .target("021-234-5678")
+        .callback("ftp://ftp.example.com")
+        .build();
+
+    let error = result.unwrap_err();
+    let errors = error.validation().expect("Expected validation error.");
+    let rules: Vec<_> = errors.iter().map(|e| (e.field, e.rule.clone())).collect();
+    assert_eq!(
+        rules,
+        [
+            ("dest_ref", Rule::InvalidCharacter('_')),
+            ("target or pay_to_ref1", Rule::BadPrefix),
+            (
+                "callback or resp_url",
+                Rule::UnsupportedScheme("ftp".into())
+            ),
+        ]
+    );
+
+    // สตริงที่ถูกต้องถูกแปลงเช่นเดียวกับการใช้ newtype
+    let request = MobileTopupRequest::builder()
+        .dest_ref("TEST01")
+        .company("TRMV")
+        .amount(Baht::from_baht(5))
+        .target("081-234-5678")
+        .callback("https://www.mywebsite.com/wepay_result.php")
+        .build()
+        .expect("Failed to build request.");
+    assert_eq!(request.target.as_str(), "0812345678");
+}
+
+#[test]
+fn test_mobile_topup_request_accepts_typed_and_string_values() {
+    let dest_ref: DestRef = "TEST01".parse().unwrap();
+    let target: MobileNumber = "081-234-5678".parse().unwrap();
+    let callback = callback();
+
+    // newtype ที่ตรวจสอบแล้วใช้ได้ทั้งแบบ move และ reference
+    let typed = MobileTopupRequest::builder()
+        .dest_ref(&dest_ref)
+        .company("TRMV")
+        .amount(Baht::from_baht(5))
+        .target(target)
+        .callback(&callback)
+        .build()
+        .expect("Failed to build request.");
+
+    let strings = MobileTopupRequest::builder()
+        .dest_ref(String::from("TEST01"))
+        .company("TRMV")
+        .amount(Baht::from_baht(5))
+        .target("0812345678")
+        .callback(callback.to_string())
+        .build()
+        .expect("Failed to build request.");
+
+    assert_eq!(typed.params(), strings.params());
+}
+
+#[test]
+fn test_mobile_topup_request_rejects_non_positive_amount() {
+    for amount in [Baht::ZERO, "-5".parse::<Baht>().unwrap()] {
+        let error = MobileTopupRequest::builder()
+            .dest_ref("TEST01")
+            .company("TRMV")
+            .amount(amount)
+            .target("081-234-5678")
+            .callback(callback())
+            .build()
+            .unwrap_err();
+        let error = error.validation().unwrap().get("amount").unwrap();
+        assert_eq!(error.rule, Rule::NotPositive);
+    }
+}
+
+#[test]
+fn test_invalid_input_rejected_before_request() {
+    let rule = |error: WepayError, field: &str| {
+        let errors = error.validation().expect("Expected validation error.");
+        errors
+            .get(field)
+            .expect("Expected field error.")
+            .rule
+            .clone()
+    };
+
+    assert_eq!(
+        rule(
+            MobileNumber::try_from("0212345678").unwrap_err(),
+            "target or pay_to_ref1"
+        ),
+        Rule::BadPrefix
+    );
+    assert_eq!(
+        rule(DestRef::try_from("TEST_01").unwrap_err(), "dest_ref"),
+        Rule::InvalidCharacter('_')
+    );
+    assert_eq!(
+        rule(
+            CallbackUrl::try_from("wepay_result.php").unwrap_err(),
+            "callback or resp_url"
+        ),
+        Rule::NotUrl
+    );
+}
+
+#[test]
+fn test_request_serialize() {
+    let request = MobileTopupRequest::builder()
+        .dest_ref("TEST01".parse::<DestRef>().unwrap())
+        .company("TRMV")
+        .amount(Baht::from_satang(484))
+        .target("0987654321".parse::<MobileNumber>().unwrap())
+        .callback(callback())
+        .build()
+        .expect("Failed to build request.");
+
+    let json = serde_json::to_value(&request).expect("Failed to serialize request.");
+    assert_eq!(json["dest_ref"], "TEST01");
+    assert_eq!(json["amount"], "4.84");
+    assert_eq!(
+        json["callback"],
+        "https://www.mywebsite.com/wepay_result.php"
+    );
+}
@@ -0,0 +1,409 @@
+#![cfg(feature = "test-support")]
+
+use axum::{Router, extract::State, routing::post};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use wepay_rs::{
+    callback::Notification,
+    catalog::{Catalog, Category},
+    error::{StatusCode, WepayError},
+    idempotency::Submission,
+    money::Baht,
+    request::{BillpayRefs, MobileTopupRequest, TransactionQuery},
+    response::TransactionStatus,
+    simulator::{Fault, Scenario, Simulator, Target},
+    types::{CallbackUrl, DestRef, MobileNumber},
+};
+
+type Received = Arc<Mutex<Vec<Notification>>>;
+
+/// เริ่มเซิร์ฟเวอร์ที่รับ callback จาก simulator และตอบกลับตามที่ wepay ต้องการ
+async fn receiver() -> (CallbackUrl, Received) {
+    async fn handle(State(received): State<Received>, body: String) -> String {
+        let notification = Notification::parse(&body).expect("Invalid callback.");
+        let ack = notification.acknowledgement();
+        received.lock().unwrap().push(notification);
+        ack
+    }
+
+    let received = Received::default();
+    let router = Router::new()
+        .route("/wepay_result", post(handle))
+        .with_state(received.clone());
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+    let url = format!("http://{}/wepay_result", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    (url.parse().unwrap(), received)
+}
+
+/// รอจนกว่าจะได้รับ callback ตามจำนวนที่กำหนด
+async fn wait_for(received: &Received, count: usize) {
+    for _ in 0..500 {
+        if received.lock().unwrap().len() >= count {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("Callback was not received.");
+}
+
+fn dest_ref(value: &str) -> DestRef {
+    DestRef::try_from(value).expect("Invalid dest_ref.")
+}
+
+fn mobile(value: &str) -> MobileNumber {
+    MobileNumber::try_from(value).expect("Invalid mobile number.")
+}
+
+fn topup_request(dest: &str, amount: i64, callback: &CallbackUrl) -> MobileTopupRequest {
+    MobileTopupRequest::builder()
+        .dest_ref(dest_ref(dest))
+        .company("TRMV")
+        .amount(Baht::from_baht(amount))
+        .target(mobile("0987654321"))
+        .callback(callback.clone())
+        .build()
+        .expect("Failed to build request.")
+}
+
+#[tokio::test]
+async fn test_simulator_topup_and_callback() {
+    let simulator = Simulator::builder()
+        .balance(Baht::from_baht(100))
+        .start()
+        .await
+        .expect("Failed to start simulator.");
+    let wepay = simulator.wepay().build().unwrap();
+    let (callback, received) = receiver().await;
+
+    let bill = wepay
+        .topup_mobile(
+            &dest_ref("SIM01"),
+            "TRMV",
+            Baht::from_baht(50),
+            &mobile("0987654321"),
+            &callback,
+        )
+        .await
+        .expect("Failed to top up.");
+    assert_eq!(bill.total_amount, Baht::from_baht(50));
+    assert_eq!(bill.balance, Baht::from_baht(50));
+
+    wait_for(&received, 1).await;
+    let notification = received.lock().unwrap()[0].clone();
+    notification
+        .verify(&dest_ref("SIM01"), &bill.transaction_id)
+        .expect("Callback does not match order.");
+    assert!(notification.is_success());
+    assert_eq!(notification.real_amount, Some(Baht::from_baht(50)));
+
+    let balance = wepay.balance().await.expect("Failed to get balance.");
+    assert_eq!(balance.available_balance, Baht::from_baht(50));
+    assert_eq!(balance.ledger_balance, Baht::from_baht(50));
+
+    let order = simulator.order("SIM01").expect("Order not found.");
+    assert_eq!(order.category, Category::Mtopup);
+    assert_eq!(order.status, TransactionStatus::Success);
+}
+
+#[tokio::test]
+async fn test_simulator_rejects_duplicates_and_insufficient_funds() {
+    let simulator = Simulator::builder()
+        .balance(Baht::from_baht(60))
+        .callback_delay(Duration::from_secs(60))
+        .start()
+        .await
+        .expect("Failed to start simulator.");
+    let wepay = simulator.wepay().build().unwrap();
+    let (callback, _received) = receiver().await;
+
+    let topup = |dest: &'static str, amount: i64| {
+        let wepay = wepay.clone();
+        let callback = callback.clone();
+        async move {
+            wepay
+                .topup_mobile(
+                    &dest_ref(dest),
+                    "TRMV",
+                    Baht::from_baht(amount),
+                    &mobile("0987654321"),
+                    &callback,
+                )
+                .await
+        }
+    };
+
+    topup("SIM01", 50).await.expect("Failed to top up.");
+
+    // รายการยังไม่เสร็จ ยอดเงินจึงถูกกันไว้แต่ยังไม่หักจากยอดคงเหลือทั้งหมด
+    let balance = wepay.balance().await.unwrap();
+    assert_eq!(balance.available_balance, Baht::from_baht(10));
+    assert_eq!(balance.ledger_balance, Baht::from_baht(60));
+
+    let error = topup("SIM01", 5).await.unwrap_err();
+    assert_eq!(error.code(), Some(&StatusCode::DuplicateCallbackRefId));
+
+    let error = topup("SIM02", 20).await.unwrap_err();
+    assert_eq!(error.code(), Some(&StatusCode::InsufficientFunds));
+
+    // ส่งซ้ำผ่าน submit_once ได้รายการเดิมจาก transaction_inquiry
+    let submission = wepay
+        .submit_once(&topup_request("SIM01", 50, &callback))
+        .await
+        .expect("Failed to resolve duplicate.");
+    assert!(matches!(
+        submission,
+        Submission::Resolved(ref transaction) if transaction.status == TransactionStatus::InProgress
+    ));
+
+    assert_eq!(simulator.orders().len(), 1);
+    assert_eq!(simulator.balance(), Baht::from_baht(10));
+}
+
+#[tokio::test]
+async fn test_simulator_failed_order_is_refunded() {
+    let simulator = Simulator::builder()
+        .balance(Baht::from_baht(100))
+        .outcome(TransactionStatus::Failed)
+        .start()
+        .await
+        .expect("Failed to start simulator.");
+    let wepay = simulator.wepay().build().unwrap();
+    let (callback, received) = receiver().await;
+
+    let index = Catalog::new(wepay.clone(), Duration::from_secs(60))
+        .index()
+        .await
+        .expect("Failed to get catalog.");
+    let company = index.billpay("12").expect("Company not found.");
+
+    let bill = wepay
+        .billpay(
+            &dest_ref("SIM01"),
+            company,
+            Baht::from_baht(20),
+            &BillpayRefs::new().ref1("020012345678"),
+            &callback,
+        )
+        .await
+        .expect("Failed to pay bill.");
+    assert_eq!(bill.total_amount, Baht::from_baht(25)); // รวมค่าธรรมเนียม
+
+    wait_for(&received, 1).await;
+    assert_eq!(
+        received.lock().unwrap()[0].status,
+        TransactionStatus::Failed
+    );
+    assert_eq!(simulator.balance(), Baht::from_baht(100));
+}
+
+#[tokio::test]
+async fn test_simulator_rejects_wrong_credentials() {
+    let simulator = Simulator::builder()
+        .start()
+        .await
+        .expect("Failed to start simulator.");
+    let wepay = simulator.wepay().password("wrong").build().unwrap();
+
+    assert!(matches!(
+        wepay.balance().await,
+        Err(WepayError::ApiError {
+            code: StatusCode::InvalidCredentialsApi,
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+async fn test_scenario_error_responses() {
+    let codes = [
+        StatusCode::InternalErrorDb,
+        StatusCode::InternalErrorIncomplete,
+        StatusCode::InternalErrorTooManyRequests,
+        StatusCode::InternalErrorCreate,
+        StatusCode::UnauthorizedIp,
+        StatusCode::InsufficientFunds,
+    ];
+    let scenario = codes.iter().fold(Scenario::new(), |scenario, code| {
+        scenario.once(Target::Balance, Fault::Error(code.clone()))
+    });
+    let simulator = Simulator::builder()
+        .scenario(
+            scenario
+                .once(
+                    Target::Balance,
+                    Fault::Http {
+                        status: 429,
+                        code: Some(StatusCode::InternalErrorTooManyRequests),
+                    },
+                )
+                .once(Target::Balance, Fault::Html { status: 502 })
+                .once(
+                    Target::Balance,
+                    Fault::Body {
+                        status: 200,
+                        content_type: "application/json".to_string(),
+                        body: r#"{"code": "10001", "ledger_balance": "0.00", "available_balance": "0.00"}"#
+                            .to_string(),
+                    },
+                )
+                .once(Target::Product, Fault::Html { status: 200 }),
+        )
+        .start()
+        .await
+        .expect("Failed to start simulator.");
+    let wepay = simulator.wepay().build().unwrap();
+
+    // error ของ wepay ใน body
+    for code in codes {
+        let error = wepay.balance().await.unwrap_err();
+        assert!(matches!(error, WepayError::ApiError { .. }));
+        assert_eq!(error.code(), Some(&code));
+    }
+
+    // HTTP status ที่ไม่สำเร็จ พร้อมและไม่พร้อมรหัสจาก wepay
+    let error = wepay.balance().await.unwrap_err();
+    assert!(matches!(
+        error,
+        WepayError::HttpError {
+            status: 429,
+            code: Some(StatusCode::InternalErrorTooManyRequests),
+            ..
+        }
+    ));
+    let error = wepay.balance().await.unwrap_err();
+    assert!(matches!(
+        error,
+        WepayError::HttpError {
+            status: 502,
+            code: None,
+            ..
+        }
+    ));
+
+    // response ครบแต่รหัสไม่ใช่ 00000
+    let error = wepay.balance().await.unwrap_err();
+    assert!(matches!(
+        error,
+        WepayError::ApiError {
+            code: StatusCode::InternalErrorDb,
+            ..
+        }
+    ));
+
+    // HTML ที่มาพร้อม HTTP 200
+    let error = wepay.product().await.unwrap_err();
+    assert!(matches!(
+        error,
+        WepayError::InvalidResponse { status: 200, .. }
+    ));
+
+    assert!(simulator.scenario().is_finished());
+    wepay.balance().await.expect("Scenario should be finished.");
+}
+
+#[tokio::test]
+async fn test_scenario_timeout_and_dropped_connection() {
+    let simulator = Simulator::builder()
+        .balance(Baht::from_baht(100))
+        .callback_delay(Duration::from_secs(60))
+        .scenario(
+            Scenario::new()
+                .once(Target::Orders, Fault::Delay(Duration::from_millis(500)))
+                .once(Target::Orders, Fault::DropConnection),
+        )
+        .start()
+        .await
+        .expect("Failed to start simulator.");
+    let wepay = simulator
+        .wepay()
+        .timeout(Duration::from_millis(200))
+        .build()
+        .unwrap();
+    let (callback, _received) = receiver().await;
+
+    // client หมดเวลารอก่อน simulator รับคำสั่งซื้อ จึงยังไม่พบรายการ
+    let error = wepay
+        .submit_once(&topup_request("SIM01", 10, &callback))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, WepayError::RequestError(ref e) if e.is_timeout()));
+
+    // หลังจากนั้น simulator รับคำสั่งซื้อแล้ว การส่งซ้ำจึงได้รายการเดิม
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let submission = wepay
+        .submit_once(&topup_request("SIM01", 10, &callback))
+        .await
+        .expect("Failed to resolve order.");
+    assert!(matches!(submission, Submission::Resolved(_)));
+
+    // connection ถูกตัดหลังรับคำสั่งซื้อ submit_once ตรวจสอบแล้วพบรายการ
+    let submission = wepay
+        .submit_once(&topup_request("SIM02", 10, &callback))
+        .await
+        .expect("Failed to resolve dropped order.");
+    assert!(matches!(submission, Submission::Resolved(_)));
+
+    assert_eq!(simulator.orders().len(), 2);
+    assert_eq!(simulator.balance(), Baht::from_baht(80));
+}
+
+#[tokio::test]
+async fn test_scenario_callback_faults() {
+    let simulator = Simulator::builder()
+        .balance(Baht::from_baht(100))
+        .scenario(
+            Scenario::new()
+                .once(Target::Orders, Fault::DuplicateCallback)
+                .once(Target::Orders, Fault::OutOfOrderCallbacks)
+                .once(Target::Orders, Fault::NoCallback),
+        )
+        .start()
+        .await
+        .expect("Failed to start simulator.");
+    let wepay = simulator.wepay().build().unwrap();
+    let (callback, received) = receiver().await;
+
+    for dest in ["SIM01", "SIM02", "SIM03"] {
+        wepay
+            .topup_mobile(
+                &dest_ref(dest),
+                "TRMV",
+                Baht::from_baht(10),
+                &mobile("0987654321"),
+                &callback,
+            )
+            .await
+            .expect("Failed to top up.");
+    }
+
+    wait_for(&received, 4).await;
+    let statuses = |dest: &str| {
+        received
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|n| n.dest_ref.as_str() == dest)
+            .map(|n| n.status)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        statuses("SIM01"),
+        [TransactionStatus::Success, TransactionStatus::Success]
+    );
+    assert_eq!(
+        statuses("SIM02"),
+        [TransactionStatus::Success, TransactionStatus::InProgress]
+    );
+
+    // ไม่ได้รับ callback แต่ตรวจสอบสถานะรายการได้
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(statuses("SIM03").is_empty());
+    let transaction = wepay
+        .transaction_status(&TransactionQuery::DestRef(dest_ref("SIM03")))
+        .await
+        .expect("Failed to get transaction status.");
+    assert_eq!(transaction.status, TransactionStatus::Success);
+}